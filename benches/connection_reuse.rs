@@ -0,0 +1,46 @@
+//! Times sequential fetches against the mock server with and without
+//! keep-alive, and checks that with it they share a single connection.
+//!
+//! ```text
+//! $ cargo bench --features mock --bench connection_reuse
+//! ```
+
+use std::time::{Duration, Instant};
+
+use ec2_instance_metadata::mock::MockImdsServer;
+
+const FETCHES: u32 = 500;
+
+// Returns the mean time per fetch and how many connections were opened.
+fn run(keep_alive: bool) -> (Duration, usize) {
+    let server = MockImdsServer::start().unwrap();
+    let client = server.client_builder().keep_alive(keep_alive).build();
+    client.get_path("meta-data/instance-id").unwrap();
+
+    let start = Instant::now();
+    for _ in 0..FETCHES {
+        client.get_path("meta-data/instance-id").unwrap();
+    }
+    (start.elapsed() / FETCHES, server.connection_count())
+}
+
+fn main() {
+    let (pooled, pooled_connections) = run(true);
+    let (unpooled, unpooled_connections) = run(false);
+
+    println!(
+        "keep-alive:    {:>8.1?}/fetch, {} connection(s)",
+        pooled, pooled_connections
+    );
+    println!(
+        "no keep-alive: {:>8.1?}/fetch, {} connection(s)",
+        unpooled, unpooled_connections
+    );
+
+    assert_eq!(
+        pooled_connections, 1,
+        "sequential fetches should share one connection"
+    );
+    // One per fetch, plus the warm-up fetch and its token request.
+    assert_eq!(unpooled_connections, FETCHES as usize + 2);
+}
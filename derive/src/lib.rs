@@ -0,0 +1,100 @@
+//! `#[derive(FromImds)]` for `ec2_instance_metadata`. Use it through the
+//! `derive` feature of that crate rather than depending on this one.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{parse_macro_input, Data, DeriveInput, Fields, LitStr};
+
+/// Implements `ec2_instance_metadata::FromImds` for a struct with named
+/// fields, each of which names the metadata path it's read from:
+///
+/// ```ignore
+/// #[derive(FromImds)]
+/// struct MyMeta {
+///     #[imds(path = "meta-data/instance-id")]
+///     id: String,
+///     #[imds(path = "meta-data/public-ipv4")]
+///     public_ip: Option<std::net::Ipv4Addr>,
+/// }
+/// ```
+#[proc_macro_derive(FromImds, attributes(imds))]
+pub fn derive_from_imds(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    input,
+                    "FromImds needs a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "FromImds can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut initializers = Vec::new();
+    for field in fields {
+        let name = &field.ident;
+        let ty = &field.ty;
+        let path = field_path(field)?;
+        initializers.push(quote! {
+            #name: <#ty as ::ec2_instance_metadata::ImdsValue>::from_imds_value(
+                client.get_path(#path),
+                #path,
+            )?
+        });
+    }
+
+    let ident = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::ec2_instance_metadata::FromImds for #ident #ty_generics #where_clause {
+            fn from_imds(
+                client: &::ec2_instance_metadata::InstanceMetadataClient,
+            ) -> ::std::result::Result<Self, ::ec2_instance_metadata::Error> {
+                ::std::result::Result::Ok(Self {
+                    #(#initializers,)*
+                })
+            }
+        }
+    })
+}
+
+// The `path` from the field's `#[imds(path = "...")]` attribute.
+fn field_path(field: &syn::Field) -> syn::Result<LitStr> {
+    let mut path = None;
+    for attr in field
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("imds"))
+    {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("path") {
+                path = Some(meta.value()?.parse::<LitStr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("expected `path = \"...\"`"))
+            }
+        })?;
+    }
+
+    path.ok_or_else(|| {
+        syn::Error::new_spanned(
+            field,
+            "missing #[imds(path = \"...\")] for the metadata path to read",
+        )
+    })
+}
@@ -0,0 +1,106 @@
+//! Serves the instance's metadata as a Prometheus info metric on
+//! `/metrics`, for node exporters to scrape alongside their own metrics.
+//!
+//! ```text
+//! $ cargo run --example prometheus_exporter --features prometheus -- 0.0.0.0:9101
+//! $ curl localhost:9101/metrics
+//! # HELP ec2_instance_info EC2 instance metadata, as labels.
+//! # TYPE ec2_instance_info gauge
+//! ec2_instance_info{instance_id="i-0123456789abcdef0",...,region="us-east-1",...} 1
+//! ```
+//!
+//! Off EC2, point it at a simulator with `AWS_EC2_METADATA_SERVICE_ENDPOINT`.
+
+use std::convert::Infallible;
+use std::error::Error;
+use std::net::SocketAddr;
+use std::time::Duration;
+
+use bytes::Bytes;
+use http_body_util::Full;
+use hyper::body::Incoming;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::{header, Request, Response, StatusCode};
+use hyper_util::rt::TokioIo;
+use tokio::net::TcpListener;
+
+use ec2_instance_metadata::InstanceMetadataClient;
+
+const DEFAULT_ADDR: &str = "127.0.0.1:9101";
+// Scrapes are usually every 15-60 seconds; there's no need for each one to
+// go to IMDS.
+const CACHE_TTL: Duration = Duration::from_secs(60);
+
+fn respond(status: StatusCode, content_type: &str, body: String) -> Response<Full<Bytes>> {
+    let mut response = Response::new(Full::new(Bytes::from(body)));
+    *response.status_mut() = status;
+    if let Ok(value) = content_type.parse() {
+        response.headers_mut().insert(header::CONTENT_TYPE, value);
+    }
+    response
+}
+
+async fn metrics(
+    client: InstanceMetadataClient,
+    request: Request<Incoming>,
+) -> Result<Response<Full<Bytes>>, Infallible> {
+    if request.uri().path() != "/metrics" {
+        return Ok(respond(
+            StatusCode::NOT_FOUND,
+            "text/plain",
+            "not found\n".into(),
+        ));
+    }
+
+    // The client blocks, so it's kept off the runtime's thread.
+    let metadata = tokio::task::spawn_blocking(move || client.get()).await;
+    Ok(match metadata {
+        Ok(Ok(metadata)) => respond(
+            StatusCode::OK,
+            "text/plain; version=0.0.4",
+            metadata.to_prometheus(),
+        ),
+        Ok(Err(e)) => respond(
+            StatusCode::SERVICE_UNAVAILABLE,
+            "text/plain",
+            format!("{}\n", e),
+        ),
+        Err(e) => respond(
+            StatusCode::INTERNAL_SERVER_ERROR,
+            "text/plain",
+            format!("{}\n", e),
+        ),
+    })
+}
+
+async fn serve(addr: SocketAddr) -> Result<(), Box<dyn Error>> {
+    let listener = TcpListener::bind(addr).await?;
+    let client = InstanceMetadataClient::builder()
+        .cache_ttl(CACHE_TTL)
+        .build();
+    eprintln!("serving instance metadata on http://{}/metrics", addr);
+
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let client = client.clone();
+        tokio::spawn(async move {
+            let service = service_fn(move |request| metrics(client.clone(), request));
+            let connection = http1::Builder::new().serve_connection(TokioIo::new(stream), service);
+            if let Err(e) = connection.await {
+                eprintln!("connection failed: {}", e);
+            }
+        });
+    }
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let addr = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_ADDR.to_string())
+        .parse()?;
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .enable_io()
+        .build()?;
+    runtime.block_on(serve(addr))
+}
@@ -0,0 +1,89 @@
+use std::time::Instant;
+
+use crate::{flat_json, AccountId, Error, InstanceMetadataClient, MetadataUrls, Result};
+
+pub(crate) const IDENTITY_DOCUMENT: &str = "dynamic/instance-identity/document";
+const IDENTITY_CREDENTIALS: &str = "meta-data/identity-credentials/ec2/info";
+const DEFAULT_ACCOUNT_ID_TAG: &str = "AccountId";
+
+/// Somewhere IMDS publishes the account id. Instance metadata options or
+/// policy can block some of these, so `get()` tries them in turn; see
+/// `InstanceMetadataClientBuilder::account_id_sources`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AccountIdSource {
+    /// The `accountId` field of `dynamic/instance-identity/document`
+    IdentityDocument,
+    /// The `AccountId` field of `meta-data/identity-credentials/ec2/info`
+    IdentityCredentials,
+    /// The value of an instance tag with this key, for hosts which only
+    /// allow access to tags
+    Tag(String),
+}
+
+impl AccountIdSource {
+    /// The identity document, then the identity credentials, then the
+    /// `AccountId` tag.
+    pub fn default_chain() -> Vec<AccountIdSource> {
+        vec![
+            AccountIdSource::IdentityDocument,
+            AccountIdSource::IdentityCredentials,
+            AccountIdSource::Tag(DEFAULT_ACCOUNT_ID_TAG.to_string()),
+        ]
+    }
+
+    pub(crate) fn path(&self) -> String {
+        match self {
+            AccountIdSource::IdentityDocument => IDENTITY_DOCUMENT.to_string(),
+            AccountIdSource::IdentityCredentials => IDENTITY_CREDENTIALS.to_string(),
+            AccountIdSource::Tag(key) => {
+                let tags: &str = MetadataUrls::Tags.into();
+                format!("{}/{}", tags, key)
+            }
+        }
+    }
+
+    fn account_id(&self, value: &str) -> Result<AccountId> {
+        let account_id = match self {
+            AccountIdSource::IdentityDocument => {
+                flat_json::required_string_field(value, "accountId")?
+            }
+            AccountIdSource::IdentityCredentials => {
+                flat_json::required_string_field(value, "AccountId")?
+            }
+            AccountIdSource::Tag(_) => value.trim().to_string(),
+        };
+        account_id.parse()
+    }
+}
+
+impl InstanceMetadataClient {
+    // Tries each source in turn, using values `get()` already fetched
+    // where it has them. If none yields an account id, the first source's
+    // error is returned, since the rest are only fallbacks.
+    pub(crate) fn fetch_account_id<F>(
+        &self,
+        prefetched: F,
+        token: &str,
+        deadline: Option<Instant>,
+    ) -> Result<AccountId>
+    where
+        F: Fn(&str) -> Option<Result<String>>,
+    {
+        let mut first_error = None;
+        for source in &self.inner.account_id_sources {
+            let path = source.path();
+            let value = match prefetched(&path) {
+                Some(value) => value,
+                None => self.fetch_within(&path, token, deadline),
+            };
+            match value.and_then(|value| source.account_id(&value)) {
+                Ok(account_id) => return Ok(account_id),
+                Err(e @ Error::DeadlineExceeded(_)) | Err(e @ Error::Cancelled) => return Err(e),
+                Err(e) => {
+                    first_error.get_or_insert(e);
+                }
+            }
+        }
+        Err(first_error.unwrap_or_else(|| Error::NotFound("no account id sources".into())))
+    }
+}
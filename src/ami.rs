@@ -0,0 +1,80 @@
+#[cfg(feature = "json")]
+use serde::Serialize;
+
+use crate::{missing_as_none, AmiId, Error, InstanceMetadataClient, Result};
+
+const AMI_ID: &str = "meta-data/ami-id";
+const AMI_LAUNCH_INDEX: &str = "meta-data/ami-launch-index";
+const AMI_MANIFEST_PATH: &str = "meta-data/ami-manifest-path";
+const KERNEL_ID: &str = "meta-data/kernel-id";
+const RAMDISK_ID: &str = "meta-data/ramdisk-id";
+const RESERVATION_ID: &str = "meta-data/reservation-id";
+
+/// `AmiInfo` holds the launch context of the instance: the AMI it booted
+/// from and the reservation it was launched in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct AmiInfo {
+    /// AWS AMI Id - always available
+    pub ami_id: AmiId,
+
+    /// Index of this instance among those launched by the same request -
+    /// always available
+    pub launch_index: u32,
+
+    /// Manifest path of instance store-backed AMIs - always available, but
+    /// `(unknown)` for EBS-backed AMIs
+    pub manifest_path: String,
+
+    /// Kernel id - only for paravirtual AMIs that specify one
+    pub kernel_id: Option<String>,
+
+    /// RAM disk id - only for paravirtual AMIs that specify one
+    pub ramdisk_id: Option<String>,
+
+    /// Reservation id - always available
+    pub reservation_id: String,
+}
+
+impl InstanceMetadataClient {
+    /// Get the AMI and boot metadata for the machine.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::Error;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set("meta-data/kernel-id", "aki-0123456789abcdef0");
+    ///
+    /// let ami = server.client().get_ami_info().unwrap();
+    /// assert_eq!(ami.ami_id, "ami-0123456789abcdef0");
+    /// assert_eq!(ami.launch_index, 0);
+    /// assert_eq!(ami.manifest_path, "(unknown)");
+    /// assert_eq!(ami.kernel_id.as_deref(), Some("aki-0123456789abcdef0"));
+    /// assert_eq!(ami.ramdisk_id, None);
+    /// assert_eq!(ami.reservation_id, "r-0123456789abcdef0");
+    ///
+    /// server.set("meta-data/ami-launch-index", "first");
+    /// let result = server.client().get_ami_info();
+    /// assert!(matches!(result, Err(Error::InvalidValue(_))));
+    /// # }
+    /// ```
+    pub fn get_ami_info(&self) -> Result<AmiInfo> {
+        let token = self.get_token()?;
+
+        let launch_index = self.fetch(AMI_LAUNCH_INDEX, &token)?;
+        let launch_index = launch_index.trim().parse().map_err(|_| {
+            Error::InvalidValue(format!("{}: {:?}", AMI_LAUNCH_INDEX, launch_index))
+        })?;
+
+        Ok(AmiInfo {
+            ami_id: self.fetch(AMI_ID, &token)?.parse()?,
+            launch_index,
+            manifest_path: self.fetch(AMI_MANIFEST_PATH, &token)?,
+            kernel_id: missing_as_none(self.fetch(KERNEL_ID, &token))?,
+            ramdisk_id: missing_as_none(self.fetch(RAMDISK_ID, &token))?,
+            reservation_id: self.fetch(RESERVATION_ID, &token)?,
+        })
+    }
+}
@@ -0,0 +1,71 @@
+//! Fetching many paths at once over a bounded number of concurrent
+//! requests.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Mutex, PoisonError};
+use std::thread;
+use std::time::Instant;
+
+use crate::{InstanceMetadataClient, Result};
+
+impl InstanceMetadataClient {
+    /// Fetch every path in `paths`, relative to `/latest/`, with one token
+    /// and at most `max_concurrency` requests in flight. Each path gets its
+    /// own result; only failing to get a token fails the whole batch.
+    /// Never cached.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::Error;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let values = server
+    ///     .client()
+    ///     .get_many(&["meta-data/instance-type", "meta-data/no-such-path"])
+    ///     .unwrap();
+    ///
+    /// assert_eq!(values["meta-data/instance-type"].as_deref().unwrap(), "t3.micro");
+    /// assert!(matches!(values["meta-data/no-such-path"], Err(Error::NotFound(_))));
+    /// # }
+    /// ```
+    pub fn get_many(&self, paths: &[&str]) -> Result<HashMap<String, Result<String>>> {
+        let token = self.get_token()?;
+        Ok(self.fetch_many(paths, &token, None))
+    }
+
+    pub(crate) fn fetch_many(
+        &self,
+        paths: &[&str],
+        token: &str,
+        deadline: Option<Instant>,
+    ) -> HashMap<String, Result<String>> {
+        let workers = self.inner.max_concurrency.clamp(1, paths.len().max(1));
+        if workers == 1 {
+            return paths
+                .iter()
+                .map(|path| (path.to_string(), self.fetch_within(path, token, deadline)))
+                .collect();
+        }
+
+        // Each worker takes the next path that nobody has started on.
+        let next = AtomicUsize::new(0);
+        let values = Mutex::new(HashMap::with_capacity(paths.len()));
+        thread::scope(|scope| {
+            for _ in 0..workers {
+                scope.spawn(|| {
+                    while let Some(path) = paths.get(next.fetch_add(1, Ordering::Relaxed)) {
+                        let value = self.fetch_within(path, token, deadline);
+                        values
+                            .lock()
+                            .unwrap_or_else(PoisonError::into_inner)
+                            .insert(path.to_string(), value);
+                    }
+                });
+            }
+        });
+
+        values.into_inner().unwrap_or_else(PoisonError::into_inner)
+    }
+}
@@ -0,0 +1,159 @@
+extern crate ec2_instance_metadata;
+
+use std::process;
+use std::time::Duration;
+
+use ec2_instance_metadata::fixture::RecordingTransport;
+use ec2_instance_metadata::{Error, InstanceMetadataClient, WatchEvent};
+
+const USAGE: &str = "Usage: ec2-metadata <command>
+
+Commands:
+  get <key>                       Print one value, e.g. `instance-id` or `placement/region`
+  ls [key]                        List the keys under a directory, `meta-data/` by default
+  all [--json|--table]            Print the instance metadata
+  export [--format <format>]      Print the instance metadata as EC2_* variables, for
+                                  `sh` (the default), `dotenv`, or `systemd`
+  tags [--json]                   Print the instance tags
+  record <file>                   Save every metadata response to a fixture file
+  watch spot [--interval <secs>]  Wait for a Spot interruption notice and print it";
+
+const DEFAULT_WATCH_INTERVAL_SECS: u64 = 5;
+
+fn usage() -> ! {
+    eprintln!("{}", USAGE);
+    process::exit(2);
+}
+
+// Bare keys are looked up under `meta-data/`, so `instance-id` and
+// `meta-data/instance-id` are equivalent.
+fn key_to_path(key: &str) -> String {
+    const TOP_LEVEL: &[&str] = &["meta-data", "dynamic", "user-data"];
+
+    let key = key.trim_start_matches('/');
+    if TOP_LEVEL.iter().any(|top| key.starts_with(top)) {
+        key.to_string()
+    } else {
+        format!("meta-data/{}", key)
+    }
+}
+
+fn print_keys(client: &InstanceMetadataClient, path: &str) -> Result<(), Error> {
+    for key in client.list_keys(path)? {
+        println!("{}", key);
+    }
+    Ok(())
+}
+
+fn to_json<T: serde::Serialize>(value: &T) -> Result<String, Error> {
+    Ok(serde_json::to_string_pretty(value)?)
+}
+
+fn watch_spot(client: InstanceMetadataClient, args: &[String]) -> Result<(), Error> {
+    let interval = match args {
+        [] => DEFAULT_WATCH_INTERVAL_SECS,
+        [flag, secs] if flag == "--interval" => secs.parse().unwrap_or_else(|_| usage()),
+        _ => usage(),
+    };
+
+    let watcher = client.watch_spot(Duration::from_secs(interval))?;
+    if let Some(action) = watcher.current().as_ref() {
+        println!("{}", to_json(action)?);
+        return Ok(());
+    }
+
+    for event in watcher.events() {
+        match event {
+            WatchEvent::Changed { current, .. } => {
+                if let Some(action) = current.as_ref() {
+                    println!("{}", to_json(action)?);
+                    return Ok(());
+                }
+            }
+            WatchEvent::RefreshFailed(e) => eprintln!("ec2-metadata: {}", e),
+        }
+    }
+
+    Ok(())
+}
+
+// Quotes `value` for the shell, which expands nothing inside single
+// quotes; embedded single quotes have to be closed, escaped and reopened.
+fn sh_quote(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "'\\''"))
+}
+
+// dotenv files and systemd's `EnvironmentFile=` both read double-quoted
+// values with backslash escapes.
+fn double_quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn export(client: &InstanceMetadataClient, args: &[String]) -> Result<(), Error> {
+    let format = match args {
+        [] => "sh",
+        [flag, format] if flag == "--format" => format.as_str(),
+        _ => usage(),
+    };
+    let line: fn(&str, &str) -> String = match format {
+        "sh" => |name, value| format!("export {}={}", name, sh_quote(value)),
+        "dotenv" | "systemd" => |name, value| format!("{}={}", name, double_quote(value)),
+        _ => usage(),
+    };
+
+    for (name, value) in client.get()?.to_env_map() {
+        println!("{}", line(&name, &value));
+    }
+    Ok(())
+}
+
+// Records what `all` and `ls -R` of `meta-data` and `dynamic` would read,
+// which is enough to reproduce most reports.
+fn record(file: &str) -> Result<(), Error> {
+    let recorder = RecordingTransport::default();
+    let client = InstanceMetadataClient::builder()
+        .transport(recorder.clone())
+        .build();
+
+    client.get()?;
+    client.get_tree("meta-data")?;
+    client.get_tree("dynamic")?;
+    recorder.fixture().save(file)
+}
+
+fn run(args: &[String]) -> Result<(), Error> {
+    let client = InstanceMetadataClient::new();
+
+    match args {
+        [cmd, key] if cmd == "get" => println!("{}", client.get_path(&key_to_path(key))?),
+        [cmd] if cmd == "ls" => print_keys(&client, "meta-data")?,
+        [cmd, key] if cmd == "ls" => print_keys(&client, &key_to_path(key))?,
+        [cmd] if cmd == "all" => println!("{}", client.get()?),
+        [cmd, flag] if cmd == "all" && flag == "--json" => println!("{}", client.get()?.to_json()?),
+        [cmd, flag] if cmd == "all" && flag == "--table" => {
+            println!("{}", client.get()?.to_pretty_table())
+        }
+        [cmd, rest @ ..] if cmd == "export" => export(&client, rest)?,
+        [cmd] if cmd == "tags" => {
+            for (key, value) in client.get_tags()? {
+                println!("{}={}", key, value);
+            }
+        }
+        [cmd, flag] if cmd == "tags" && flag == "--json" => {
+            println!("{}", to_json(&client.get_tags()?)?)
+        }
+        [cmd, file] if cmd == "record" => record(file)?,
+        [cmd, what, rest @ ..] if cmd == "watch" && what == "spot" => watch_spot(client, rest)?,
+        _ => usage(),
+    }
+
+    Ok(())
+}
+
+fn main() {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if let Err(e) = run(&args) {
+        eprintln!("ec2-metadata: {}", e);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::BTreeMap;
+use std::str::FromStr;
+
+#[cfg(feature = "json")]
+use serde::{Serialize, Serializer};
+
+use crate::{Error, InstanceMetadataClient, Result};
+
+const BLOCK_DEVICE_MAPPING: &str = "meta-data/block-device-mapping";
+
+/// `BlockDeviceRole` is the role of a volume in the instance's block
+/// device mapping, as named by IMDS.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum BlockDeviceRole {
+    /// The virtual device holding the AMI's root image, `ami`
+    Ami,
+    /// The root device, `root`
+    Root,
+    /// The swap device, `swap`
+    Swap,
+    /// An attached EBS volume, `ebsN`
+    Ebs(u32),
+    /// An instance store volume, `ephemeralN`
+    Ephemeral(u32),
+    /// A role this crate doesn't know about yet
+    Other(String),
+}
+
+impl FromStr for BlockDeviceRole {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let numbered = |prefix: &str| s.strip_prefix(prefix).and_then(|n| n.parse().ok());
+
+        Ok(match s {
+            "ami" => BlockDeviceRole::Ami,
+            "root" => BlockDeviceRole::Root,
+            "swap" => BlockDeviceRole::Swap,
+            _ => {
+                if let Some(n) = numbered("ebs") {
+                    BlockDeviceRole::Ebs(n)
+                } else if let Some(n) = numbered("ephemeral") {
+                    BlockDeviceRole::Ephemeral(n)
+                } else {
+                    BlockDeviceRole::Other(s.to_string())
+                }
+            }
+        })
+    }
+}
+
+impl std::fmt::Display for BlockDeviceRole {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            BlockDeviceRole::Ami => write!(f, "ami"),
+            BlockDeviceRole::Root => write!(f, "root"),
+            BlockDeviceRole::Swap => write!(f, "swap"),
+            BlockDeviceRole::Ebs(n) => write!(f, "ebs{}", n),
+            BlockDeviceRole::Ephemeral(n) => write!(f, "ephemeral{}", n),
+            BlockDeviceRole::Other(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for BlockDeviceRole {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Maps each volume role to its device name, e.g. `Root` to `/dev/xvda`.
+pub type BlockDeviceMapping = BTreeMap<BlockDeviceRole, String>;
+
+impl InstanceMetadataClient {
+    /// Get the block device mapping the instance was launched with.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::BlockDeviceRole;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set("meta-data/block-device-mapping/ebs2", "sdb");
+    ///
+    /// let mapping = server.client().get_block_device_mapping().unwrap();
+    /// assert_eq!(mapping[&BlockDeviceRole::Ami], "/dev/xvda");
+    /// assert_eq!(mapping[&BlockDeviceRole::Root], "/dev/xvda");
+    /// assert_eq!(mapping[&BlockDeviceRole::Ebs(2)], "sdb");
+    /// assert_eq!(mapping.len(), 3);
+    /// # }
+    /// ```
+    pub fn get_block_device_mapping(&self) -> Result<BlockDeviceMapping> {
+        let token = self.get_token()?;
+        let roles = self.fetch(BLOCK_DEVICE_MAPPING, &token)?;
+
+        let mut mapping = BlockDeviceMapping::new();
+        for role in roles.lines().filter(|r| !r.is_empty()) {
+            let path = format!("{}/{}", BLOCK_DEVICE_MAPPING, role);
+            let device = self.fetch(&path, &token)?;
+            mapping.insert(role.parse()?, device);
+        }
+
+        Ok(mapping)
+    }
+}
@@ -0,0 +1,107 @@
+//! Fetching the metadata at boot, when IMDS may not be answering yet.
+
+use std::time::{Duration, Instant};
+
+use crate::{detect, Error, InstanceMetadata, InstanceMetadataClient, Result};
+
+const FIRST_BOOT_RETRY_DELAY: Duration = Duration::from_millis(250);
+const MAX_BOOT_RETRY_DELAY: Duration = Duration::from_secs(2);
+
+// Failures IMDS gives while the instance is still coming up: the network
+// isn't configured yet, or IMDS answers but isn't ready to serve
+// metadata.
+fn is_warming_up(error: &Error) -> bool {
+    match error {
+        Error::HttpRequest(_) | Error::IoError(_) | Error::Timeout(_) => true,
+        Error::HttpStatus(status, _) => *status == 429 || *status >= 500,
+        _ => false,
+    }
+}
+
+// Connection failures on a machine whose DMI data says it isn't an EC2
+// instance won't go away by waiting.
+fn is_not_ec2(error: &Error) -> bool {
+    let unreachable = matches!(
+        error,
+        Error::HttpRequest(_) | Error::IoError(_) | Error::Timeout(_)
+    );
+    unreachable && detect::dmi_check() == Some(false)
+}
+
+impl InstanceMetadataClient {
+    /// Get the instance metadata, waiting up to `timeout` for IMDS to
+    /// become ready. Meant for init systems and bootstrap code, which can
+    /// run before the network is up or before IMDS serves metadata.
+    ///
+    /// Connection failures, timeouts, throttling and server errors are
+    /// retried with a growing delay until `timeout` passes, and then fail
+    /// with `Error::DeadlineExceeded`. Failures waiting won't fix, such as
+    /// `Error::NotFound` or `Error::HopLimitExceeded`, are returned right
+    /// away, as are connection failures on machines whose DMI data shows
+    /// they aren't EC2 instances, which fail with `Error::NotEc2`. The
+    /// client's own `deadline`, if shorter, still limits each attempt.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set_status("meta-data/instance-id", 503);
+    ///
+    /// let metadata = thread::scope(|scope| {
+    ///     scope.spawn(|| {
+    ///         thread::sleep(Duration::from_millis(500));
+    ///         server.remove("meta-data/instance-id");
+    ///         server.set("meta-data/instance-id", "i-0123456789abcdef0");
+    ///     });
+    ///     server.client().get_with_deadline(Duration::from_secs(10))
+    /// });
+    /// assert_eq!(metadata.unwrap().instance_id, "i-0123456789abcdef0");
+    ///
+    /// // The error names the deadline given here, not the client's.
+    /// server.set_status("meta-data/instance-id", 503);
+    /// let error = server.client().get_with_deadline(Duration::from_millis(300));
+    /// let message = error.unwrap_err().to_string();
+    /// assert!(message.contains("IMDS wasn't ready within 300ms"), "{}", message);
+    /// # }
+    /// ```
+    pub fn get_with_deadline(&self, timeout: Duration) -> Result<InstanceMetadata> {
+        let deadline = Instant::now() + timeout;
+        let mut delay = FIRST_BOOT_RETRY_DELAY;
+
+        loop {
+            let attempt_deadline = match self.operation_deadline() {
+                Some(client_deadline) => client_deadline.min(deadline),
+                None => deadline,
+            };
+            let error = match self.get_within(Some(attempt_deadline)) {
+                Ok(metadata) => return Ok(metadata),
+                Err(error) => error,
+            };
+
+            // An attempt cut short by the client's deadline is retried like
+            // any other slow response.
+            let retryable = is_warming_up(&error) || matches!(error, Error::DeadlineExceeded(_));
+            if !retryable {
+                return Err(error);
+            }
+            if is_not_ec2(&error) {
+                return Err(Error::NotEc2(format!(
+                    "DMI data rules out EC2 and IMDS is unreachable: {}",
+                    error
+                )));
+            }
+            if Instant::now() + delay >= deadline {
+                return Err(Error::DeadlineExceeded(format!(
+                    "IMDS wasn't ready within {:?}: {}",
+                    timeout, error
+                )));
+            }
+
+            self.inner.cancel.sleep(delay)?;
+            delay = (delay * 2).min(MAX_BOOT_RETRY_DELAY);
+        }
+    }
+}
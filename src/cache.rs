@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+use crate::InstanceMetadata;
+
+#[derive(Debug)]
+struct CacheEntry<T> {
+    value: T,
+    fetched_at: Instant,
+}
+
+impl<T: Clone> CacheEntry<T> {
+    fn new(value: T) -> Self {
+        Self {
+            value,
+            fetched_at: Instant::now(),
+        }
+    }
+
+    fn fresh(&self, ttl: Duration) -> Option<T> {
+        if self.fetched_at.elapsed() < ttl {
+            Some(self.value.clone())
+        } else {
+            None
+        }
+    }
+}
+
+/// In-memory TTL cache for the full metadata snapshot and for values
+/// fetched by path.
+#[derive(Debug)]
+pub(crate) struct Cache {
+    ttl: Duration,
+    metadata: Mutex<Option<CacheEntry<InstanceMetadata>>>,
+    paths: Mutex<HashMap<String, CacheEntry<String>>>,
+}
+
+// A panic while holding one of these locks can't leave the cache in an
+// inconsistent state, so there's no reason to propagate the poison.
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl Cache {
+    pub(crate) fn new(ttl: Duration) -> Self {
+        Self {
+            ttl,
+            metadata: Mutex::new(None),
+            paths: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub(crate) fn get_metadata(&self) -> Option<InstanceMetadata> {
+        lock(&self.metadata)
+            .as_ref()
+            .and_then(|entry| entry.fresh(self.ttl))
+    }
+
+    pub(crate) fn put_metadata(&self, metadata: InstanceMetadata) {
+        *lock(&self.metadata) = Some(CacheEntry::new(metadata));
+    }
+
+    pub(crate) fn get_path(&self, path: &str) -> Option<String> {
+        lock(&self.paths)
+            .get(path)
+            .and_then(|entry| entry.fresh(self.ttl))
+    }
+
+    pub(crate) fn put_path(&self, path: &str, value: String) {
+        let mut paths = lock(&self.paths);
+        paths.retain(|_, entry| entry.fetched_at.elapsed() < self.ttl);
+        paths.insert(path.to_string(), CacheEntry::new(value));
+    }
+
+    pub(crate) fn clear(&self) {
+        *lock(&self.metadata) = None;
+        lock(&self.paths).clear();
+    }
+}
@@ -0,0 +1,85 @@
+//! Cancelling a client's requests from another thread, so services can shut
+//! down without waiting out timeouts and retries against an unreachable
+//! IMDS.
+
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use crate::{Error, Result};
+
+/// `CancellationToken` cancels the requests of every client built with it.
+/// Clones share their state, so one can be kept to cancel with while
+/// another is handed to the builder.
+///
+/// Once cancelled, requests fail with `Error::Cancelled` instead of being
+/// sent, and backoff between retries ends early. A request already on the
+/// wire still runs until it's answered or times out. Watchers stop
+/// refreshing once their client is cancelled.
+///
+/// # Examples:
+/// ```
+/// # #[cfg(feature = "mock")] {
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// use ec2_instance_metadata::{CancellationToken, Error};
+/// let server = MockImdsServer::start().unwrap();
+/// let token = CancellationToken::new();
+/// let client = server.client_builder().cancellation_token(token.clone()).build();
+/// client.get_path("meta-data/instance-id").unwrap();
+///
+/// token.cancel();
+/// let result = client.get_path("meta-data/instance-id");
+/// assert!(matches!(result, Err(Error::Cancelled)));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<State>);
+
+#[derive(Debug, Default)]
+struct State {
+    cancelled: Mutex<bool>,
+    wake: Condvar,
+}
+
+impl CancellationToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Cancel every client sharing this token. Can't be undone.
+    pub fn cancel(&self) {
+        *self.lock() = true;
+        self.0.wake.notify_all();
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        *self.lock()
+    }
+
+    pub(crate) fn check(&self) -> Result<()> {
+        if self.is_cancelled() {
+            return Err(Error::Cancelled);
+        }
+        Ok(())
+    }
+
+    // Sleeps for `duration`, waking early and failing if the token is
+    // cancelled in the meantime.
+    pub(crate) fn sleep(&self, duration: Duration) -> Result<()> {
+        let cancelled = self.lock();
+        let (cancelled, _) = self
+            .0
+            .wake
+            .wait_timeout_while(cancelled, duration, |cancelled| !*cancelled)
+            .unwrap_or_else(|e| e.into_inner());
+        if *cancelled {
+            return Err(Error::Cancelled);
+        }
+        Ok(())
+    }
+
+    // A panic elsewhere can't leave the flag half-written, so a poisoned
+    // lock is still safe to use.
+    fn lock(&self) -> std::sync::MutexGuard<'_, bool> {
+        self.0.cancelled.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
@@ -0,0 +1,93 @@
+//! A provider-neutral view of instance metadata, so agents running on more
+//! than one cloud can depend on a single crate.
+//!
+//! `InstanceMetadataClient` always implements `CloudMetadata`. Google
+//! Compute Engine and Azure implementations are available behind the `gcp`
+//! and `azure` features.
+
+#[cfg(feature = "json")]
+use serde::Serialize;
+
+use crate::{InstanceMetadataClient, Result};
+
+#[cfg(feature = "azure")]
+mod azure;
+#[cfg(feature = "gcp")]
+mod gcp;
+
+#[cfg(feature = "azure")]
+pub use self::azure::AzureMetadataClient;
+#[cfg(feature = "gcp")]
+pub use self::gcp::GcpMetadataClient;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+#[cfg_attr(feature = "json", serde(rename_all = "lowercase"))]
+pub enum CloudProvider {
+    Aws,
+    Gcp,
+    Azure,
+}
+
+impl std::fmt::Display for CloudProvider {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            CloudProvider::Aws => "aws",
+            CloudProvider::Gcp => "gcp",
+            CloudProvider::Azure => "azure",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// `CloudInstance` holds the fields every supported cloud can provide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct CloudInstance {
+    pub provider: CloudProvider,
+
+    /// EC2 instance id, GCE instance id, or Azure VM id
+    pub instance_id: String,
+
+    /// AWS account id, GCP project id, or Azure subscription id
+    pub account_id: String,
+
+    /// e.g. `us-east-1`, `us-central1`, or `eastus`
+    pub region: String,
+
+    /// e.g. `us-east-1a` or `us-central1-a`. On Azure this is the zone
+    /// number, and is empty for VMs not deployed to an availability zone.
+    pub zone: String,
+
+    /// EC2 instance type, GCE machine type, or Azure VM size
+    pub machine_type: String,
+
+    pub hostname: String,
+}
+
+/// `CloudMetadata` is implemented by each provider's metadata client.
+pub trait CloudMetadata {
+    fn provider(&self) -> CloudProvider;
+
+    fn get_instance(&self) -> Result<CloudInstance>;
+}
+
+impl CloudMetadata for InstanceMetadataClient {
+    fn provider(&self) -> CloudProvider {
+        CloudProvider::Aws
+    }
+
+    fn get_instance(&self) -> Result<CloudInstance> {
+        let metadata = self.get()?;
+
+        Ok(CloudInstance {
+            provider: CloudProvider::Aws,
+            instance_id: metadata.instance_id.into(),
+            account_id: metadata.account_id.map(String::from).unwrap_or_default(),
+            region: metadata.region.to_string(),
+            zone: metadata.availability_zone.into(),
+            machine_type: metadata.instance_type.to_string(),
+            hostname: metadata.hostname.unwrap_or_default(),
+        })
+    }
+}
@@ -0,0 +1,138 @@
+use std::sync::Arc;
+
+use serde_json::Value;
+
+use super::{CloudInstance, CloudMetadata, CloudProvider};
+use crate::transport::{self, ImdsTransport, Method, Request, TransportConfig};
+use crate::{check_status, stream, Error, Result, DEFAULT_MAX_BODY_SIZE};
+
+const DEFAULT_ENDPOINT: &str = "http://169.254.169.254";
+const API_VERSION: &str = "2021-02-01";
+
+/// `AzureMetadataClient` fetches instance metadata from the Azure Instance
+/// Metadata Service. Responses larger than 1 MiB fail with
+/// `Error::MalformedResponse`.
+#[derive(Debug, Clone)]
+pub struct AzureMetadataClient {
+    transport: Arc<dyn ImdsTransport>,
+    endpoint: String,
+}
+
+impl AzureMetadataClient {
+    pub fn new() -> Self {
+        let transport = transport::default_transport(&TransportConfig::default());
+        Self {
+            transport: transport.into(),
+            endpoint: DEFAULT_ENDPOINT.to_string(),
+        }
+    }
+
+    /// Create a client for a different IMDS endpoint, sending requests
+    /// through `transport`.
+    pub fn with_transport(
+        endpoint: impl Into<String>,
+        transport: impl ImdsTransport + 'static,
+    ) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            endpoint: endpoint.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Get the full `metadata/instance` document. Fails with
+    /// `Error::NotFound` for a 404 and `Error::HttpStatus` for any other
+    /// unsuccessful status.
+    ///
+    /// # Examples:
+    /// ```
+    /// use ec2_instance_metadata::cloud::{AzureMetadataClient, CloudMetadata};
+    /// use ec2_instance_metadata::transport::{ImdsTransport, Request, Response};
+    /// use ec2_instance_metadata::Error;
+    ///
+    /// // Answers every request with the same response.
+    /// #[derive(Debug)]
+    /// struct Fixed(u16, &'static str);
+    ///
+    /// impl ImdsTransport for Fixed {
+    ///     fn send(&self, _request: &Request) -> Result<Response, Error> {
+    ///         Ok(Response {
+    ///             status: self.0,
+    ///             headers: Vec::new(),
+    ///             body: self.1.into(),
+    ///         })
+    ///     }
+    /// }
+    ///
+    /// let document = r#"{"compute": {"vmId": "02aab8a4-74ef-476e-8182-f6d2ba4166a6",
+    ///     "subscriptionId": "8d10da13-8125-4ba9-a717-bf7490507b3d", "location": "eastus",
+    ///     "zone": "1", "vmSize": "Standard_D2s_v3", "name": "web-1"}}"#;
+    /// let client = |status, body| {
+    ///     AzureMetadataClient::with_transport("http://169.254.169.254", Fixed(status, body))
+    /// };
+    ///
+    /// let instance = client(200, document).get_instance().unwrap();
+    /// assert_eq!(instance.region, "eastus");
+    /// assert_eq!(instance.hostname, "web-1");
+    ///
+    /// let missing = client(404, "").get_document();
+    /// assert!(matches!(missing, Err(Error::NotFound(_))));
+    /// let failed = client(500, "").get_document();
+    /// assert!(matches!(failed, Err(Error::HttpStatus(500, _))));
+    /// ```
+    pub fn get_document(&self) -> Result<Value> {
+        let url = format!(
+            "{}/metadata/instance?api-version={}",
+            self.endpoint, API_VERSION
+        );
+        let request = Request::new(Method::Get, url).header("Metadata", "true");
+
+        let resp = stream::send_bounded(
+            &*self.transport,
+            &request,
+            DEFAULT_MAX_BODY_SIZE,
+            "metadata/instance",
+        )?;
+        check_status(resp.status, "metadata/instance")?;
+
+        Ok(serde_json::from_slice(&resp.body)?)
+    }
+}
+
+impl Default for AzureMetadataClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl CloudMetadata for AzureMetadataClient {
+    fn provider(&self) -> CloudProvider {
+        CloudProvider::Azure
+    }
+
+    fn get_instance(&self) -> Result<CloudInstance> {
+        let document = self.get_document()?;
+        let compute = &document["compute"];
+
+        let field = |name: &str| {
+            compute[name]
+                .as_str()
+                .map(|s| s.to_string())
+                .ok_or_else(|| Error::JsonError(format!("Missing compute.{} field", name)))
+        };
+
+        let hostname = match compute["osProfile"]["computerName"].as_str() {
+            Some(name) if !name.is_empty() => name.to_string(),
+            _ => field("name")?,
+        };
+
+        Ok(CloudInstance {
+            provider: CloudProvider::Azure,
+            instance_id: field("vmId")?,
+            account_id: field("subscriptionId")?,
+            region: field("location")?,
+            zone: compute["zone"].as_str().unwrap_or_default().to_string(),
+            machine_type: field("vmSize")?,
+            hostname,
+        })
+    }
+}
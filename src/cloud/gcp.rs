@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use super::{CloudInstance, CloudMetadata, CloudProvider};
+use crate::transport::{self, ImdsTransport, Method, Request, TransportConfig};
+use crate::{check_status, stream, Error, Result, DEFAULT_MAX_BODY_SIZE};
+
+const DEFAULT_BASE_URL: &str = "http://metadata.google.internal/computeMetadata/v1/";
+
+/// `GcpMetadataClient` fetches instance metadata from the Google Compute
+/// Engine metadata server. Responses larger than 1 MiB fail with
+/// `Error::MalformedResponse`.
+#[derive(Debug, Clone)]
+pub struct GcpMetadataClient {
+    transport: Arc<dyn ImdsTransport>,
+    base_url: String,
+}
+
+impl GcpMetadataClient {
+    pub fn new() -> Self {
+        let transport = transport::default_transport(&TransportConfig::default());
+        Self {
+            transport: transport.into(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+        }
+    }
+
+    /// Create a client for a different metadata server base URL (ending in
+    /// `computeMetadata/v1/`), sending requests through `transport`.
+    pub fn with_transport(
+        base_url: impl Into<String>,
+        transport: impl ImdsTransport + 'static,
+    ) -> Self {
+        let base_url = format!("{}/", base_url.into().trim_end_matches('/'));
+        Self {
+            transport: Arc::new(transport),
+            base_url,
+        }
+    }
+
+    /// Get the value at `path`, relative to `computeMetadata/v1/`. Fails
+    /// with `Error::NotFound` for a 404 and `Error::HttpStatus` for any
+    /// other unsuccessful status.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::cloud::{CloudMetadata, GcpMetadataClient};
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::Error;
+    /// // The mock serves whatever values it's given, so it can stand in
+    /// // for the metadata server.
+    /// let server = MockImdsServer::start_empty().unwrap();
+    /// server.allow_imdsv1(true);
+    /// server.set("instance/id", "4520031799277581759");
+    /// server.set("instance/zone", "projects/123456789012/zones/us-central1-a");
+    /// server.set("instance/machine-type", "projects/123456789012/machineTypes/e2-medium");
+    /// server.set("instance/hostname", "web-1.us-central1-a.c.my-project.internal");
+    /// server.set("project/project-id", "my-project");
+    /// let base_url = "http://metadata.google.internal/latest";
+    /// let client = GcpMetadataClient::with_transport(base_url, server.transport());
+    ///
+    /// let instance = client.get_instance().unwrap();
+    /// assert_eq!(instance.region, "us-central1");
+    /// assert_eq!(instance.zone, "us-central1-a");
+    /// assert_eq!(instance.machine_type, "e2-medium");
+    ///
+    /// let missing = client.get_path("instance/no-such-path");
+    /// assert!(matches!(missing, Err(Error::NotFound(_))));
+    /// server.set_status("instance/id", 500);
+    /// let failed = client.get_path("instance/id");
+    /// assert!(matches!(failed, Err(Error::HttpStatus(500, _))));
+    /// # }
+    /// ```
+    pub fn get_path(&self, path: &str) -> Result<String> {
+        let url = format!("{}{}", self.base_url, path);
+        let request = Request::new(Method::Get, url).header("Metadata-Flavor", "Google");
+
+        let resp = stream::send_bounded(&*self.transport, &request, DEFAULT_MAX_BODY_SIZE, path)?;
+        check_status(resp.status, path)?;
+
+        resp.text()
+    }
+}
+
+impl Default for GcpMetadataClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Zones and machine types come back as resource paths, e.g.
+// `projects/123/zones/us-central1-a`.
+fn last_segment(resource: &str) -> &str {
+    resource.rsplit('/').next().unwrap_or(resource)
+}
+
+impl CloudMetadata for GcpMetadataClient {
+    fn provider(&self) -> CloudProvider {
+        CloudProvider::Gcp
+    }
+
+    fn get_instance(&self) -> Result<CloudInstance> {
+        let zone = last_segment(&self.get_path("instance/zone")?).to_string();
+        let region = match zone.rsplit_once('-') {
+            Some((region, _)) => region.to_string(),
+            None => return Err(Error::UnknownAvailabilityZone(zone)),
+        };
+
+        Ok(CloudInstance {
+            provider: CloudProvider::Gcp,
+            instance_id: self.get_path("instance/id")?,
+            account_id: self.get_path("project/project-id")?,
+            region,
+            zone,
+            machine_type: last_segment(&self.get_path("instance/machine-type")?).to_string(),
+            hostname: self.get_path("instance/hostname")?,
+        })
+    }
+}
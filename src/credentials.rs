@@ -0,0 +1,193 @@
+use std::sync::{Mutex, MutexGuard, PoisonError, TryLockError};
+use std::time::{Duration, SystemTime};
+
+use crate::flat_json::required_string_field;
+use crate::timestamp::parse_rfc3339;
+use crate::{Error, InstanceMetadataClient, Result};
+
+const SECURITY_CREDENTIALS: &str =
+    "meta-data/identity-credentials/ec2/security-credentials/ec2-instance";
+
+// Refresh this long before the credentials expire, matching the AWS SDKs.
+const DEFAULT_REFRESH_MARGIN_SECS: u64 = 5 * 60;
+
+/// `Credentials` are the instance identity credentials AWS issues to every
+/// instance. They identify the instance itself rather than an IAM role, and
+/// are mostly useful to AWS services and agents that authenticate the
+/// instance.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Credentials {
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    pub token: String,
+
+    /// Expiration time as reported by IMDS, e.g. `2024-01-01T06:00:00Z`
+    pub expiration: String,
+
+    /// `expiration`, parsed
+    pub expires_at: SystemTime,
+}
+
+impl Credentials {
+    /// True if the credentials expire within `margin` from now.
+    pub fn expires_within(&self, margin: Duration) -> bool {
+        match self.expires_at.duration_since(SystemTime::now()) {
+            Ok(remaining) => remaining <= margin,
+            Err(_) => true,
+        }
+    }
+}
+
+// Keep the secret parts out of logs.
+impl std::fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Credentials")
+            .field("access_key_id", &self.access_key_id)
+            .field("secret_access_key", &"** redacted **")
+            .field("token", &"** redacted **")
+            .field("expiration", &self.expiration)
+            .finish()
+    }
+}
+
+fn parse_credentials(body: &str) -> Result<Credentials> {
+    let field = |name: &str| required_string_field(body, name);
+
+    let expiration = field("Expiration")?;
+    let expires_at = parse_rfc3339(&expiration)
+        .ok_or_else(|| Error::InvalidValue(format!("Expiration: {:?}", expiration)))?;
+
+    Ok(Credentials {
+        access_key_id: field("AccessKeyId")?,
+        secret_access_key: field("SecretAccessKey")?,
+        token: field("Token")?,
+        expiration,
+        expires_at,
+    })
+}
+
+/// `IdentityCredentials` hands out the instance identity credentials,
+/// fetching new ones shortly before the current ones expire. If a refresh
+/// fails, the current credentials are handed out until they actually
+/// expire. It's safe to share between threads; only one caller refreshes
+/// at a time, and the others get the current credentials meanwhile, or
+/// wait for the refresh if those have expired.
+///
+/// # Examples:
+/// ```
+/// # #[cfg(feature = "mock")] {
+/// use std::time::Duration;
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// const PATH: &str = "meta-data/identity-credentials/ec2/security-credentials/ec2-instance";
+/// let credentials = |key: &str, expiration: &str| {
+///     format!(
+///         r#"{{"AccessKeyId":"{}","SecretAccessKey":"secret","Token":"token","Expiration":"{}"}}"#,
+///         key, expiration
+///     )
+/// };
+/// let server = MockImdsServer::start().unwrap();
+/// server.set(PATH, &credentials("ASIAFIRST", "2099-01-01T00:00:00Z"));
+///
+/// // A margin longer than the credentials last, so every call refreshes.
+/// let margin = Duration::from_secs(200 * 365 * 24 * 60 * 60);
+/// let client = server.client_builder().max_attempts(1).build();
+/// let identity = client.identity_credentials().with_refresh_margin(margin);
+/// assert_eq!(identity.get().unwrap().access_key_id, "ASIAFIRST");
+///
+/// server.set(PATH, &credentials("ASIASECOND", "2099-01-01T00:00:00Z"));
+/// assert_eq!(identity.get().unwrap().access_key_id, "ASIASECOND");
+///
+/// // The refresh fails, but the current credentials haven't expired.
+/// server.set_status(PATH, 503);
+/// assert_eq!(identity.get().unwrap().access_key_id, "ASIASECOND");
+///
+/// // Once they have, the failure is returned.
+/// server.remove(PATH);
+/// server.set(PATH, &credentials("ASIAEXPIRED", "2000-01-01T00:00:00Z"));
+/// assert_eq!(identity.get().unwrap().access_key_id, "ASIAEXPIRED");
+/// server.set_status(PATH, 503);
+/// assert!(identity.get().is_err());
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct IdentityCredentials {
+    client: InstanceMetadataClient,
+    refresh_margin: Duration,
+    current: Mutex<Option<Credentials>>,
+    // Held while refreshing, so only one caller fetches at a time without
+    // `current` being locked for the whole request.
+    refresh: Mutex<()>,
+}
+
+impl IdentityCredentials {
+    /// Refresh the credentials this long before they expire, instead of the
+    /// default 5 minutes.
+    pub fn with_refresh_margin(mut self, margin: Duration) -> Self {
+        self.refresh_margin = margin;
+        self
+    }
+
+    /// Get credentials valid for at least the refresh margin, or the
+    /// current ones if they can't be refreshed and haven't expired yet.
+    pub fn get(&self) -> Result<Credentials> {
+        let current = self.current();
+        if let Some(credentials) = &current {
+            if !credentials.expires_within(self.refresh_margin) {
+                return Ok(credentials.clone());
+            }
+        }
+        // Still-valid credentials need no wait for someone else's refresh.
+        let still_valid = current.filter(|c| !c.expires_within(Duration::ZERO));
+
+        let _refresh = match (self.refresh.try_lock(), still_valid) {
+            (Ok(guard), _) => guard,
+            (Err(TryLockError::Poisoned(e)), _) => e.into_inner(),
+            (Err(TryLockError::WouldBlock), Some(credentials)) => return Ok(credentials),
+            (Err(TryLockError::WouldBlock), None) => {
+                let guard = self.refresh.lock().unwrap_or_else(PoisonError::into_inner);
+                // The refresh just waited for may have succeeded.
+                if let Some(credentials) = self.current() {
+                    if !credentials.expires_within(self.refresh_margin) {
+                        return Ok(credentials);
+                    }
+                }
+                guard
+            }
+        };
+
+        let refreshed = self
+            .client
+            .fetch_path(SECURITY_CREDENTIALS)
+            .and_then(|body| parse_credentials(&body));
+        match refreshed {
+            Ok(credentials) => {
+                *self.lock() = Some(credentials.clone());
+                Ok(credentials)
+            }
+            Err(e) => self
+                .current()
+                .filter(|c| !c.expires_within(Duration::ZERO))
+                .ok_or(e),
+        }
+    }
+
+    fn current(&self) -> Option<Credentials> {
+        self.lock().clone()
+    }
+
+    fn lock(&self) -> MutexGuard<'_, Option<Credentials>> {
+        self.current.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+impl InstanceMetadataClient {
+    /// Wrap this client in a self-refreshing `IdentityCredentials`.
+    pub fn identity_credentials(self) -> IdentityCredentials {
+        IdentityCredentials {
+            client: self,
+            refresh_margin: Duration::from_secs(DEFAULT_REFRESH_MARGIN_SECS),
+            current: Mutex::new(None),
+            refresh: Mutex::new(()),
+        }
+    }
+}
@@ -0,0 +1,116 @@
+use std::fs;
+use std::time::Duration;
+
+use crate::InstanceMetadataClient;
+
+const PROBE_TIMEOUT_MS: u64 = 250;
+
+// Nitro instances report "Amazon EC2" as the board/system vendor. Xen
+// instances prefix their UUIDs with "ec2", in either case depending on
+// how the UUID was formatted, but anyone can pick such a UUID for their
+// own VM, so it's only a hint.
+const DMI_VENDOR_FILES: &[&str] = &[
+    "/sys/class/dmi/id/board_vendor",
+    "/sys/class/dmi/id/sys_vendor",
+];
+const UUID_FILES: &[&str] = &["/sys/hypervisor/uuid", "/sys/class/dmi/id/product_uuid"];
+
+fn read_trimmed(path: &str) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+// Nitro instances also expose their instance id as the board asset tag,
+// which can be read without a round trip to IMDS.
+#[cfg(feature = "json")]
+const ASSET_TAG_FILE: &str = "/sys/class/dmi/id/board_asset_tag";
+
+#[cfg(feature = "json")]
+pub(crate) fn dmi_instance_id() -> Option<String> {
+    read_trimmed(ASSET_TAG_FILE).filter(|tag| tag.starts_with("i-"))
+}
+
+fn dmi_vendors() -> Vec<String> {
+    DMI_VENDOR_FILES
+        .iter()
+        .filter_map(|path| read_trimmed(path))
+        .collect()
+}
+
+// Whether the DMI and hypervisor identifiers suggest this could be an EC2
+// instance, or `None` if none of them could be read, e.g. in a sandbox
+// without `/sys`.
+pub(crate) fn dmi_check() -> Option<bool> {
+    let vendors = dmi_vendors();
+    let uuids: Vec<String> = UUID_FILES
+        .iter()
+        .filter_map(|path| read_trimmed(path))
+        .collect();
+    if vendors.is_empty() && uuids.is_empty() {
+        return None;
+    }
+
+    let vendor_match = vendors.iter().any(|vendor| vendor == "Amazon EC2");
+    let uuid_match = uuids
+        .iter()
+        .any(|uuid| uuid.to_ascii_lowercase().starts_with("ec2"));
+    Some(vendor_match || uuid_match)
+}
+
+// Only the vendor is trusted without asking IMDS.
+fn dmi_says_ec2() -> bool {
+    dmi_vendors().iter().any(|vendor| vendor == "Amazon EC2")
+}
+
+/// Returns true if the current machine looks like an EC2 instance.
+///
+/// The local DMI/SMBIOS vendor is checked first, which needs no network
+/// access. If it isn't "Amazon EC2", a single token request is sent to
+/// IMDS, which must answer within 250ms. It isn't retried, and a failure
+/// isn't diagnosed with further requests. A Xen hypervisor UUID starting
+/// with "ec2" doesn't settle it on its own. When
+/// `AWS_EC2_METADATA_SERVICE_ENDPOINT` points somewhere other than IMDS,
+/// only that endpoint is asked.
+///
+/// # Examples:
+/// ```no_run
+/// if ec2_instance_metadata::is_ec2() {
+///     let client = ec2_instance_metadata::InstanceMetadataClient::new();
+///     println!("{}", client.get().unwrap());
+/// }
+/// ```
+///
+/// ```
+/// # #[cfg(feature = "mock")] {
+/// use std::time::Duration;
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// use ec2_instance_metadata::is_ec2_with_timeout;
+/// let server = MockImdsServer::start().unwrap();
+/// std::env::set_var("AWS_EC2_METADATA_SERVICE_ENDPOINT", server.endpoint());
+/// assert!(is_ec2_with_timeout(Duration::from_secs(1)));
+/// assert_eq!(server.request_count(), 1);
+///
+/// // The token request's error isn't retried.
+/// server.set_status("api/token", 503);
+/// assert!(!is_ec2_with_timeout(Duration::from_secs(1)));
+/// assert_eq!(server.request_count(), 2);
+/// # }
+/// ```
+pub fn is_ec2() -> bool {
+    is_ec2_with_timeout(Duration::from_millis(PROBE_TIMEOUT_MS))
+}
+
+/// Same as `is_ec2()`, with a custom deadline for the IMDS probe.
+pub fn is_ec2_with_timeout(probe_timeout: Duration) -> bool {
+    let client = InstanceMetadataClient::builder()
+        .timeout(probe_timeout)
+        .deadline(probe_timeout)
+        .max_attempts(1)
+        .build();
+    if client.on_imds() && dmi_says_ec2() {
+        return true;
+    }
+
+    // Other clouds serve their own metadata on 169.254.169.254 too, so only
+    // a successful IMDSv2 token exchange counts.
+    client.token_probe(client.operation_deadline())
+}
@@ -0,0 +1,148 @@
+use std::fmt;
+
+use crate::{InstanceMetadata, WatchEvent};
+
+/// A field whose value differs between two snapshots. `None` means the
+/// field wasn't served in that snapshot, e.g. a public hostname that was
+/// added or released.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldChange {
+    /// The field's kebab-case name, as in `InstanceMetadata::fields`.
+    pub field: &'static str,
+    pub previous: Option<String>,
+    pub current: Option<String>,
+}
+
+/// `MetadataDiff` lists the fields that changed between two
+/// `InstanceMetadata` snapshots, in the order they're printed in.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct MetadataDiff {
+    pub changes: Vec<FieldChange>,
+}
+
+impl MetadataDiff {
+    /// True if the snapshots were the same.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// The change to `field`, if it changed.
+    pub fn get(&self, field: &str) -> Option<&FieldChange> {
+        self.changes.iter().find(|change| change.field == field)
+    }
+}
+
+/// One `field: previous -> current` line per change, with `-` for a
+/// missing value.
+impl fmt::Display for MetadataDiff {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let value = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".into());
+        for (i, change) in self.changes.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{}: {} -> {}",
+                change.field,
+                value(&change.previous),
+                value(&change.current)
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl InstanceMetadata {
+    /// The fields that differ between `self`, the earlier snapshot, and
+    /// `other`.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let before = server.client().get().unwrap();
+    /// server.remove("meta-data/public-hostname");
+    /// server.set("meta-data/instance-type", "t3.large");
+    /// let after = server.client().get().unwrap();
+    ///
+    /// let diff = before.diff(&after);
+    /// assert_eq!(diff.get("instance-type").unwrap().current.as_deref(), Some("t3.large"));
+    /// assert_eq!(diff.get("public-hostname").unwrap().current, None);
+    /// assert_eq!(diff.changes.len(), 2);
+    /// # }
+    /// ```
+    ///
+    /// A public IP that changed, e.g. when an Elastic IP was associated,
+    /// and updated tags:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set("meta-data/public-ipv4", "203.0.113.1");
+    /// server.set("meta-data/tags/instance/Name", "web");
+    /// let before = server.client().get().unwrap();
+    /// server.set("meta-data/public-ipv4", "198.51.100.7");
+    /// server.set("meta-data/tags/instance/Env", "prod");
+    /// let after = server.client().get().unwrap();
+    /// assert_eq!(after.tags["Env"], "prod");
+    ///
+    /// let diff = before.diff(&after);
+    /// let public_ipv4 = diff.get("public-ipv4").unwrap();
+    /// assert_eq!(public_ipv4.previous.as_deref(), Some("203.0.113.1"));
+    /// assert_eq!(public_ipv4.current.as_deref(), Some("198.51.100.7"));
+    /// let tags = diff.get("tags").unwrap();
+    /// assert_eq!(tags.previous.as_deref(), Some("Name=web"));
+    /// assert_eq!(tags.current.as_deref(), Some("Env=prod,Name=web"));
+    /// assert_eq!(diff.changes.len(), 2);
+    /// # }
+    /// ```
+    pub fn diff(&self, other: &InstanceMetadata) -> MetadataDiff {
+        let previous = self.fields();
+        let current = other.fields();
+        let lookup = |fields: &[(&'static str, String)], field: &str| {
+            fields
+                .iter()
+                .find(|(key, _)| *key == field)
+                .map(|(_, value)| value.clone())
+        };
+
+        // Fields only present in `other` come after the rest.
+        let mut keys: Vec<&'static str> = previous.iter().map(|(key, _)| *key).collect();
+        for (key, _) in &current {
+            if !keys.contains(key) {
+                keys.push(key);
+            }
+        }
+
+        let changes = keys
+            .into_iter()
+            .filter_map(|field| {
+                let before = lookup(&previous, field);
+                let after = lookup(&current, field);
+                if before == after {
+                    None
+                } else {
+                    Some(FieldChange {
+                        field,
+                        previous: before,
+                        current: after,
+                    })
+                }
+            })
+            .collect();
+
+        MetadataDiff { changes }
+    }
+}
+
+impl WatchEvent<InstanceMetadata> {
+    /// What changed, for `Changed` events.
+    pub fn diff(&self) -> Option<MetadataDiff> {
+        match self {
+            WatchEvent::Changed { previous, current } => Some(previous.diff(current)),
+            WatchEvent::RefreshFailed(_) => None,
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use std::fs;
+use std::path::PathBuf;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::InstanceMetadata;
+
+// What's written to disk: the snapshot plus when it was fetched, so a
+// later process can tell whether it's still fresh.
+#[derive(Serialize, Deserialize)]
+struct Snapshot {
+    fetched_at: u64,
+    metadata: InstanceMetadata,
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+/// A metadata snapshot persisted to a file, shared by every process on
+/// the host which uses the same path. Failing to read or write the file
+/// never fails a request; the snapshot is just fetched from IMDS instead.
+#[derive(Debug)]
+pub(crate) struct DiskCache {
+    path: PathBuf,
+    ttl: Duration,
+}
+
+impl DiskCache {
+    pub(crate) fn new(path: PathBuf, ttl: Duration) -> Self {
+        Self { path, ttl }
+    }
+
+    /// The cached snapshot, unless it's missing, unreadable, or older
+    /// than the TTL.
+    pub(crate) fn load(&self) -> Option<InstanceMetadata> {
+        let contents = fs::read(&self.path).ok()?;
+        let snapshot: Snapshot = serde_json::from_slice(&contents).ok()?;
+
+        let age = now_secs().saturating_sub(snapshot.fetched_at);
+        if age < self.ttl.as_secs() {
+            Some(snapshot.metadata)
+        } else {
+            None
+        }
+    }
+
+    pub(crate) fn store(&self, metadata: &InstanceMetadata) {
+        let snapshot = Snapshot {
+            fetched_at: now_secs(),
+            metadata: metadata.clone(),
+        };
+        let contents = match serde_json::to_vec(&snapshot) {
+            Ok(contents) => contents,
+            Err(_) => return,
+        };
+
+        // Write to a temporary file and rename it into place, so a reader
+        // never sees a partly written snapshot.
+        let mut tmp = self.path.clone().into_os_string();
+        tmp.push(format!(".{}.tmp", std::process::id()));
+        if fs::write(&tmp, contents).is_err() || fs::rename(&tmp, &self.path).is_err() {
+            let _ = fs::remove_file(&tmp);
+        }
+    }
+
+    pub(crate) fn clear(&self) {
+        let _ = fs::remove_file(&self.path);
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::btree_map::{self, BTreeMap};
+
+#[cfg(feature = "json")]
+use serde::Serialize;
+
+use crate::{InstanceMetadataClient, Result};
+
+const DYNAMIC: &str = "dynamic";
+const INSTANCE_MONITORING: &str = "fws/instance-monitoring";
+const IDENTITY_DOCUMENT: &str = "instance-identity/document";
+const IDENTITY_SIGNATURE: &str = "instance-identity/signature";
+const IDENTITY_PKCS7: &str = "instance-identity/pkcs7";
+const IDENTITY_RSA2048: &str = "instance-identity/rsa2048";
+
+/// `DynamicData` holds everything under `dynamic/`, keyed by path relative
+/// to it (e.g. `fws/instance-monitoring`), with accessors for the values
+/// this crate knows how to read.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "json", derive(Serialize), serde(transparent))]
+pub struct DynamicData {
+    values: BTreeMap<String, String>,
+}
+
+impl DynamicData {
+    /// The value at `path`, relative to `dynamic/`.
+    pub fn get(&self, path: &str) -> Option<&str> {
+        self.values.get(path.trim_matches('/')).map(String::as_str)
+    }
+
+    /// Every path and its value, in path order.
+    pub fn iter(&self) -> btree_map::Iter<'_, String, String> {
+        self.values.iter()
+    }
+
+    /// True if detailed CloudWatch monitoring is enabled, from
+    /// `fws/instance-monitoring`.
+    pub fn detailed_monitoring(&self) -> Option<bool> {
+        match self.get(INSTANCE_MONITORING)?.trim() {
+            "enabled" => Some(true),
+            "disabled" => Some(false),
+            _ => None,
+        }
+    }
+
+    /// The instance identity document, as the JSON IMDS served.
+    pub fn identity_document(&self) -> Option<&str> {
+        self.get(IDENTITY_DOCUMENT)
+    }
+
+    /// The SHA256 RSA signature of the identity document, base64-encoded.
+    pub fn identity_signature(&self) -> Option<&str> {
+        self.get(IDENTITY_SIGNATURE)
+    }
+
+    /// The identity document's PKCS7 signature, which can be verified
+    /// against the region's public DSA certificate.
+    pub fn identity_pkcs7(&self) -> Option<&str> {
+        self.get(IDENTITY_PKCS7)
+    }
+
+    /// The identity document's PKCS7 signature, which can be verified
+    /// against the region's public RSA certificate.
+    pub fn identity_rsa2048(&self) -> Option<&str> {
+        self.get(IDENTITY_RSA2048)
+    }
+}
+
+impl IntoIterator for DynamicData {
+    type Item = (String, String);
+    type IntoIter = btree_map::IntoIter<String, String>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.values.into_iter()
+    }
+}
+
+impl InstanceMetadataClient {
+    /// Fetch everything under `dynamic/`, such as the instance identity
+    /// document and the monitoring state.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set("dynamic/fws/instance-monitoring", "enabled");
+    ///
+    /// let dynamic = server.client().get_dynamic().unwrap();
+    /// assert_eq!(dynamic.detailed_monitoring(), Some(true));
+    /// assert!(dynamic.identity_document().unwrap().contains("i-0123456789abcdef0"));
+    /// # }
+    /// ```
+    pub fn get_dynamic(&self) -> Result<DynamicData> {
+        let prefix = format!("{}/", DYNAMIC);
+        let values = self
+            .get_tree(DYNAMIC)?
+            .into_iter()
+            .map(|(path, value)| match path.strip_prefix(&prefix) {
+                Some(relative) => (relative.to_string(), value),
+                None => (path, value),
+            })
+            .collect();
+
+        Ok(DynamicData { values })
+    }
+}
@@ -0,0 +1,206 @@
+//! Support for the ECS task metadata endpoint (version 4), available to
+//! containers running on ECS, both on EC2 and on Fargate:
+//! https://docs.aws.amazon.com/AmazonECS/latest/developerguide/task-metadata-endpoint-v4.html
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::transport::{self, ImdsTransport, Method, Request, TransportConfig};
+use crate::{check_status, stream, Error, Result, DEFAULT_MAX_BODY_SIZE};
+
+/// The environment variable ECS injects into every container with the
+/// base URL of the metadata endpoint.
+pub const ECS_METADATA_ENV_VAR: &str = "ECS_CONTAINER_METADATA_URI_V4";
+
+/// CPU and memory limits of a task or container.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+pub struct Limits {
+    /// CPU units for containers, vCPUs for tasks
+    #[serde(rename = "CPU", default)]
+    pub cpu: Option<f64>,
+
+    /// Memory in MiB
+    #[serde(default)]
+    pub memory: Option<u64>,
+}
+
+/// A network attachment of a container.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct ContainerNetwork {
+    /// `awsvpc`, `bridge`, `host`, or `none`
+    pub network_mode: String,
+
+    #[serde(rename = "IPv4Addresses")]
+    pub ipv4_addresses: Vec<String>,
+
+    #[serde(rename = "IPv6Addresses")]
+    pub ipv6_addresses: Vec<String>,
+
+    /// Only set in `awsvpc` mode
+    #[serde(rename = "MACAddress")]
+    pub mac_address: Option<String>,
+
+    /// Only set in `awsvpc` mode
+    #[serde(rename = "IPv4SubnetCIDRBlock")]
+    pub ipv4_subnet_cidr_block: Option<String>,
+
+    /// Only set in `awsvpc` mode
+    #[serde(rename = "PrivateDNSName")]
+    pub private_dns_name: Option<String>,
+}
+
+/// `ContainerMetadata` describes a single container in a task.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct ContainerMetadata {
+    pub docker_id: String,
+    pub name: String,
+    pub docker_name: String,
+    pub image: String,
+
+    #[serde(rename = "ImageID")]
+    pub image_id: String,
+
+    #[serde(rename = "ContainerARN")]
+    pub container_arn: Option<String>,
+
+    pub labels: BTreeMap<String, String>,
+    pub desired_status: String,
+    pub known_status: String,
+    pub limits: Limits,
+    pub created_at: Option<String>,
+    pub started_at: Option<String>,
+    pub networks: Vec<ContainerNetwork>,
+}
+
+/// `TaskMetadata` describes the task the current container belongs to.
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct TaskMetadata {
+    /// Cluster name or ARN
+    pub cluster: String,
+
+    #[serde(rename = "TaskARN")]
+    pub task_arn: String,
+
+    pub family: String,
+    pub revision: String,
+    pub desired_status: String,
+    pub known_status: String,
+
+    /// Task-level limits, if any were set in the task definition
+    pub limits: Option<Limits>,
+
+    pub availability_zone: Option<String>,
+
+    /// `EC2` or `FARGATE`
+    pub launch_type: Option<String>,
+
+    pub containers: Vec<ContainerMetadata>,
+}
+
+/// `EcsMetadataClient` fetches metadata from the ECS task metadata
+/// endpoint. Responses larger than 1 MiB fail with
+/// `Error::MalformedResponse`.
+///
+/// # Examples:
+/// ```no_run
+/// use ec2_instance_metadata::ecs::EcsMetadataClient;
+/// let client = EcsMetadataClient::from_env().expect("not running on ECS");
+/// let task = client.get_task().unwrap();
+/// println!("{} in {}", task.task_arn, task.cluster);
+/// ```
+#[derive(Debug, Clone)]
+pub struct EcsMetadataClient {
+    transport: Arc<dyn ImdsTransport>,
+    base_url: String,
+}
+
+impl EcsMetadataClient {
+    /// Create a client for the endpoint in `ECS_CONTAINER_METADATA_URI_V4`.
+    /// Fails with `Error::NotFound` if the variable isn't set, which means
+    /// the process isn't running in an ECS container.
+    pub fn from_env() -> Result<Self> {
+        std::env::var(ECS_METADATA_ENV_VAR)
+            .map(Self::new)
+            .map_err(|_| Error::NotFound(ECS_METADATA_ENV_VAR.to_string()))
+    }
+
+    /// Create a client for the given metadata endpoint URL.
+    pub fn new(base_url: impl Into<String>) -> Self {
+        let transport = transport::default_transport(&TransportConfig::default());
+        Self {
+            transport: transport.into(),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Create a client which sends requests through `transport`.
+    pub fn with_transport(
+        base_url: impl Into<String>,
+        transport: impl ImdsTransport + 'static,
+    ) -> Self {
+        Self {
+            transport: Arc::new(transport),
+            base_url: base_url.into().trim_end_matches('/').to_string(),
+        }
+    }
+
+    fn fetch<T: DeserializeOwned>(&self, suffix: &str) -> Result<T> {
+        let url = format!("{}{}", self.base_url, suffix);
+        let request = Request::new(Method::Get, url);
+        let path = if suffix.is_empty() { "/" } else { suffix };
+        let resp = stream::send_bounded(&*self.transport, &request, DEFAULT_MAX_BODY_SIZE, path)?;
+        check_status(resp.status, path)?;
+
+        Ok(serde_json::from_slice(&resp.body)?)
+    }
+
+    /// Get the metadata of the container this process is running in.
+    pub fn get_container(&self) -> Result<ContainerMetadata> {
+        self.fetch("")
+    }
+
+    /// Get the metadata of the task this process is running in, including
+    /// every container in it. Fails with `Error::NotFound` for a 404 and
+    /// `Error::HttpStatus` for any other unsuccessful status.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::ecs::EcsMetadataClient;
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::Error;
+    /// // The mock serves whatever values it's given, so it can stand in
+    /// // for the task metadata endpoint.
+    /// let server = MockImdsServer::start_empty().unwrap();
+    /// server.allow_imdsv1(true);
+    /// server.set(
+    ///     "v4/task",
+    ///     r#"{"Cluster": "default", "Family": "web", "Revision": "3",
+    ///         "TaskARN": "arn:aws:ecs:us-east-1:123456789012:task/default/0123",
+    ///         "DesiredStatus": "RUNNING", "KnownStatus": "RUNNING",
+    ///         "Containers": [{"Name": "app", "Image": "nginx:latest"}]}"#,
+    /// );
+    /// let client_for = |url| EcsMetadataClient::with_transport(url, server.transport());
+    /// let client = client_for("http://169.254.170.2/latest/v4");
+    ///
+    /// let task = client.get_task().unwrap();
+    /// assert_eq!(task.family, "web");
+    /// assert_eq!(task.containers[0].image, "nginx:latest");
+    ///
+    /// let elsewhere = client_for("http://169.254.170.2/latest/v3");
+    /// assert!(matches!(elsewhere.get_task(), Err(Error::NotFound(_))));
+    /// server.set_status("v4/task", 500);
+    /// assert!(matches!(client.get_task(), Err(Error::HttpStatus(500, _))));
+    /// # }
+    /// ```
+    pub fn get_task(&self) -> Result<TaskMetadata> {
+        self.fetch("/task")
+    }
+}
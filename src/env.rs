@@ -0,0 +1,220 @@
+//! The environment variables the AWS SDKs read to configure their IMDS
+//! clients. `InstanceMetadataClientBuilder::build` reads them too, so the
+//! crate behaves the same as the SDKs in environments configured for
+//! them. Anything set explicitly on the builder takes precedence.
+
+use std::time::Duration;
+
+/// Overrides the IMDS endpoint, e.g. `http://127.0.0.1:1338`.
+///
+/// # Examples:
+/// ```
+/// # #[cfg(feature = "mock")] {
+/// use ec2_instance_metadata::env::ENDPOINT_VAR;
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// use ec2_instance_metadata::InstanceMetadataClient;
+/// let server = MockImdsServer::start().unwrap();
+/// std::env::set_var(ENDPOINT_VAR, server.endpoint());
+/// let metadata = InstanceMetadataClient::new().get().unwrap();
+/// assert_eq!(metadata.instance_id, "i-0123456789abcdef0");
+///
+/// // The builder takes precedence.
+/// let other = MockImdsServer::start().unwrap();
+/// other.set("meta-data/instance-id", "i-0fedcba9876543210");
+/// let client = InstanceMetadataClient::builder()
+///     .endpoint(other.endpoint())
+///     .build();
+/// assert_eq!(client.get().unwrap().instance_id, "i-0fedcba9876543210");
+/// # }
+/// ```
+pub const ENDPOINT_VAR: &str = "AWS_EC2_METADATA_SERVICE_ENDPOINT";
+
+/// `IPv4` or `IPv6`; picks the default endpoint when `ENDPOINT_VAR` isn't
+/// set. Any other value is ignored.
+///
+/// # Examples:
+/// ```
+/// use std::sync::{Arc, Mutex};
+/// use ec2_instance_metadata::env::ENDPOINT_MODE_VAR;
+/// use ec2_instance_metadata::transport::Request;
+/// use ec2_instance_metadata::{EndpointMode, Error, InstanceMetadataClient, Middleware};
+///
+/// // Notes where the client would send each request, without sending it.
+/// struct Urls(Arc<Mutex<Vec<String>>>);
+///
+/// impl Middleware for Urls {
+///     fn before_send(&self, request: &mut Request) -> Result<(), Error> {
+///         self.0.lock().unwrap().push(request.url.clone());
+///         Err(Error::Cancelled)
+///     }
+/// }
+///
+/// let first_url = |mode: &str| {
+///     std::env::set_var(ENDPOINT_MODE_VAR, mode);
+///     let urls = Arc::new(Mutex::new(Vec::new()));
+///     let client = InstanceMetadataClient::builder()
+///         .middleware(Urls(Arc::clone(&urls)))
+///         .build();
+///     let _ = client.get_path("meta-data/instance-id");
+///     let first = urls.lock().unwrap()[0].clone();
+///     first
+/// };
+/// assert_eq!(first_url("IPv6"), "http://[fd00:ec2::254]/latest/api/token");
+///
+/// // An unknown mode leaves the default endpoint in place.
+/// assert!(matches!("IPv5".parse::<EndpointMode>(), Err(Error::InvalidValue(_))));
+/// assert_eq!(first_url("IPv5"), "http://169.254.169.254/latest/api/token");
+/// ```
+pub const ENDPOINT_MODE_VAR: &str = "AWS_EC2_METADATA_SERVICE_ENDPOINT_MODE";
+
+/// Timeout for each request, in seconds, e.g. `0.5`. Values which aren't
+/// positive numbers, or are too large for a `Duration`, are ignored.
+///
+/// # Examples:
+/// ```
+/// use std::net::TcpListener;
+/// use std::time::{Duration, Instant};
+/// use ec2_instance_metadata::env::TIMEOUT_VAR;
+/// use ec2_instance_metadata::{Error, InstanceMetadataClient};
+///
+/// // Accepts connections but never answers.
+/// let silent = TcpListener::bind("127.0.0.1:0").unwrap();
+/// std::env::set_var(TIMEOUT_VAR, "0.2");
+/// let client = InstanceMetadataClient::builder()
+///     .endpoint(format!("http://{}", silent.local_addr().unwrap()))
+///     .max_attempts(1)
+///     .build();
+///
+/// let started = Instant::now();
+/// let result = client.get_path("meta-data/instance-id");
+/// assert!(matches!(result, Err(Error::Timeout(_))), "{:?}", result);
+/// // Well short of the default 2s timeout.
+/// assert!(started.elapsed() < Duration::from_secs(2));
+///
+/// // Too large a value leaves the default in place.
+/// # #[cfg(feature = "mock")] {
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// std::env::set_var(TIMEOUT_VAR, "1e300");
+/// let server = MockImdsServer::start().unwrap();
+/// assert!(server.client().get_path("meta-data/instance-id").is_ok());
+/// # }
+/// ```
+pub const TIMEOUT_VAR: &str = "AWS_METADATA_SERVICE_TIMEOUT";
+
+/// How many times to try each request, including the first. Values
+/// which aren't positive integers are ignored.
+///
+/// # Examples:
+/// ```
+/// # #[cfg(feature = "mock")] {
+/// use ec2_instance_metadata::env::NUM_ATTEMPTS_VAR;
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// let attempts = |value: &str| {
+///     std::env::set_var(NUM_ATTEMPTS_VAR, value);
+///     let server = MockImdsServer::start().unwrap();
+///     server.set_status("meta-data/instance-id", 503);
+///     let _ = server.client().get_path("meta-data/instance-id");
+///     // Less the token request.
+///     server.request_count() - 1
+/// };
+/// assert_eq!(attempts("1"), 1);
+/// assert_eq!(attempts("2"), 2);
+///
+/// // Zero is ignored, leaving the default of 3.
+/// assert_eq!(attempts("0"), 3);
+/// # }
+/// ```
+pub const NUM_ATTEMPTS_VAR: &str = "AWS_METADATA_SERVICE_NUM_ATTEMPTS";
+
+/// `true` turns off every IMDS request. Any other value leaves them on.
+///
+/// # Examples:
+/// ```
+/// # #[cfg(feature = "mock")] {
+/// use ec2_instance_metadata::env::DISABLED_VAR;
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// use ec2_instance_metadata::Error;
+/// let server = MockImdsServer::start().unwrap();
+///
+/// std::env::set_var(DISABLED_VAR, "true");
+/// assert!(matches!(server.client().get(), Err(Error::Disabled)));
+/// assert_eq!(server.request_count(), 0);
+///
+/// // The builder takes precedence.
+/// assert!(server.client_builder().disabled(false).build().get().is_ok());
+///
+/// std::env::set_var(DISABLED_VAR, "yes");
+/// assert!(server.client().get().is_ok());
+/// # }
+/// ```
+pub const DISABLED_VAR: &str = "AWS_EC2_METADATA_DISABLED";
+
+/// `EndpointMode` selects which of the two IMDS addresses the client
+/// talks to by default.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EndpointMode {
+    /// `http://169.254.169.254`
+    #[default]
+    IPv4,
+    /// `http://[fd00:ec2::254]`, only reachable on Nitro instances with
+    /// the IPv6 endpoint enabled
+    IPv6,
+}
+
+impl EndpointMode {
+    pub fn endpoint(self) -> &'static str {
+        match self {
+            EndpointMode::IPv4 => "http://169.254.169.254",
+            EndpointMode::IPv6 => "http://[fd00:ec2::254]",
+        }
+    }
+}
+
+impl std::str::FromStr for EndpointMode {
+    type Err = crate::Error;
+
+    fn from_str(s: &str) -> crate::Result<Self> {
+        if s.eq_ignore_ascii_case("ipv4") {
+            Ok(EndpointMode::IPv4)
+        } else if s.eq_ignore_ascii_case("ipv6") {
+            Ok(EndpointMode::IPv6)
+        } else {
+            Err(crate::Error::InvalidValue(format!("endpoint mode {}", s)))
+        }
+    }
+}
+
+/// Settings read from the environment. Unset variables and values that
+/// can't be parsed are both `None`.
+#[derive(Debug, Default)]
+pub(crate) struct EnvConfig {
+    pub(crate) endpoint: Option<String>,
+    pub(crate) endpoint_mode: Option<EndpointMode>,
+    pub(crate) timeout: Option<Duration>,
+    pub(crate) num_attempts: Option<u32>,
+    pub(crate) disabled: Option<bool>,
+}
+
+fn var(name: &str) -> Option<String> {
+    std::env::var(name)
+        .ok()
+        .map(|value| value.trim().to_string())
+        .filter(|value| !value.is_empty())
+}
+
+impl EnvConfig {
+    pub(crate) fn from_env() -> Self {
+        Self {
+            endpoint: var(ENDPOINT_VAR),
+            endpoint_mode: var(ENDPOINT_MODE_VAR).and_then(|mode| mode.parse().ok()),
+            timeout: var(TIMEOUT_VAR)
+                .and_then(|secs| secs.parse::<f64>().ok())
+                .filter(|secs| *secs > 0.0)
+                .and_then(|secs| Duration::try_from_secs_f64(secs).ok()),
+            num_attempts: var(NUM_ATTEMPTS_VAR)
+                .and_then(|n| n.parse().ok())
+                .filter(|n| *n > 0),
+            disabled: var(DISABLED_VAR).map(|value| value.eq_ignore_ascii_case("true")),
+        }
+    }
+}
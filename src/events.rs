@@ -0,0 +1,256 @@
+use std::time::SystemTime;
+
+use crate::flat_json::{self, required_string_field, string_field};
+use crate::spot::REBALANCE_RECOMMENDATION;
+use crate::timestamp::{parse_event_time, parse_rfc3339};
+use crate::{missing_as_none, Error, InstanceMetadataClient, Result};
+
+const SCHEDULED_EVENTS: &str = "meta-data/events/maintenance/scheduled";
+const EVENT_HISTORY: &str = "meta-data/events/maintenance/history";
+
+/// What a scheduled maintenance event will do to the instance.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EventCode {
+    InstanceReboot,
+    SystemReboot,
+    SystemMaintenance,
+    InstanceRetirement,
+    InstanceStop,
+    /// A code this crate doesn't know about yet
+    Other(String),
+}
+
+impl EventCode {
+    pub fn as_str(&self) -> &str {
+        match self {
+            EventCode::InstanceReboot => "instance-reboot",
+            EventCode::SystemReboot => "system-reboot",
+            EventCode::SystemMaintenance => "system-maintenance",
+            EventCode::InstanceRetirement => "instance-retirement",
+            EventCode::InstanceStop => "instance-stop",
+            EventCode::Other(s) => s,
+        }
+    }
+}
+
+impl From<&str> for EventCode {
+    fn from(s: &str) -> Self {
+        match s.trim() {
+            "instance-reboot" => EventCode::InstanceReboot,
+            "system-reboot" => EventCode::SystemReboot,
+            "system-maintenance" => EventCode::SystemMaintenance,
+            "instance-retirement" => EventCode::InstanceRetirement,
+            "instance-stop" => EventCode::InstanceStop,
+            other => EventCode::Other(other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for EventCode {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// Where a maintenance event is in its life.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum EventState {
+    /// Scheduled and yet to happen, or under way
+    Active,
+    Completed,
+    Canceled,
+    /// A state this crate doesn't know about yet
+    Other(String),
+}
+
+impl EventState {
+    pub fn as_str(&self) -> &str {
+        match self {
+            EventState::Active => "active",
+            EventState::Completed => "completed",
+            EventState::Canceled => "canceled",
+            EventState::Other(s) => s,
+        }
+    }
+}
+
+impl From<&str> for EventState {
+    fn from(s: &str) -> Self {
+        match s.trim() {
+            "active" => EventState::Active,
+            "completed" => EventState::Completed,
+            "canceled" => EventState::Canceled,
+            other => EventState::Other(other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for EventState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+/// `InstanceEvent` is a maintenance event AWS scheduled for the instance,
+/// from `events/maintenance/`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceEvent {
+    /// e.g. `instance-event-0d59937288b749b32`
+    pub id: Option<String>,
+    pub code: EventCode,
+    pub state: EventState,
+    pub description: Option<String>,
+
+    /// The start of the event's window
+    pub not_before: Option<SystemTime>,
+
+    /// The end of the event's window
+    pub not_after: Option<SystemTime>,
+
+    /// The latest the event can be rescheduled to start, for events which
+    /// can be rescheduled
+    pub not_before_deadline: Option<SystemTime>,
+}
+
+/// `InstanceEvents` gathers the instance's upcoming and past maintenance
+/// events and any rebalance recommendation.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct InstanceEvents {
+    /// Events yet to happen or under way
+    pub scheduled: Vec<InstanceEvent>,
+
+    /// Events which completed or were canceled recently
+    pub history: Vec<InstanceEvent>,
+
+    /// When EC2 signalled that the Spot instance is at elevated risk of
+    /// interruption
+    pub rebalance_recommended_at: Option<SystemTime>,
+}
+
+impl InstanceEvents {
+    /// True if IMDS reported no events at all.
+    pub fn is_empty(&self) -> bool {
+        self.scheduled.is_empty()
+            && self.history.is_empty()
+            && self.rebalance_recommended_at.is_none()
+    }
+
+    /// The scheduled events which are still active, soonest first.
+    pub fn upcoming(&self) -> Vec<&InstanceEvent> {
+        let mut upcoming: Vec<&InstanceEvent> = self
+            .scheduled
+            .iter()
+            .filter(|event| event.state == EventState::Active)
+            .collect();
+        upcoming.sort_by_key(|event| event.not_before);
+        upcoming
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl InstanceEvent {
+    /// `not_before` as a `chrono` UTC date-time.
+    pub fn not_before_utc(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.not_before.map(Into::into)
+    }
+
+    /// `not_after` as a `chrono` UTC date-time.
+    pub fn not_after_utc(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.not_after.map(Into::into)
+    }
+
+    /// `not_before_deadline` as a `chrono` UTC date-time.
+    pub fn not_before_deadline_utc(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.not_before_deadline.map(Into::into)
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl InstanceEvents {
+    /// `rebalance_recommended_at` as a `chrono` UTC date-time.
+    pub fn rebalance_recommended_at_utc(&self) -> Option<chrono::DateTime<chrono::Utc>> {
+        self.rebalance_recommended_at.map(Into::into)
+    }
+}
+
+// Parses an optional time field of a maintenance event.
+fn event_time(event: &str, name: &str) -> Result<Option<SystemTime>> {
+    match string_field(event, name)? {
+        Some(time) => parse_event_time(&time)
+            .map(Some)
+            .ok_or_else(|| Error::InvalidValue(format!("{}: {:?}", name, time))),
+        None => Ok(None),
+    }
+}
+
+fn parse_events(body: &str) -> Result<Vec<InstanceEvent>> {
+    flat_json::objects(body)?
+        .into_iter()
+        .map(|event| {
+            Ok(InstanceEvent {
+                id: string_field(event, "EventId")?,
+                code: EventCode::from(required_string_field(event, "Code")?.as_str()),
+                state: EventState::from(required_string_field(event, "State")?.as_str()),
+                description: string_field(event, "Description")?,
+                not_before: event_time(event, "NotBefore")?,
+                not_after: event_time(event, "NotAfter")?,
+                not_before_deadline: event_time(event, "NotBeforeDeadline")?,
+            })
+        })
+        .collect()
+}
+
+impl InstanceMetadataClient {
+    /// Get the instance's scheduled and past maintenance events and its
+    /// rebalance recommendation.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::{EventCode, EventState};
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set(
+    ///     "meta-data/events/maintenance/scheduled",
+    ///     r#"[{"NotBefore": "21 Jan 2019 09:00:43 GMT", "Code": "system-reboot",
+    ///          "Description": "scheduled reboot", "EventId": "instance-event-0d59937288b749b32",
+    ///          "NotAfter": "21 Jan 2019 09:17:23 GMT", "State": "active"}]"#,
+    /// );
+    ///
+    /// let events = server.client().get_events().unwrap();
+    /// let upcoming = events.upcoming();
+    /// assert_eq!(upcoming[0].code, EventCode::SystemReboot);
+    /// assert_eq!(upcoming[0].state, EventState::Active);
+    /// let not_before = UNIX_EPOCH + Duration::from_secs(1_548_061_243);
+    /// assert_eq!(upcoming[0].not_before, Some(not_before));
+    /// assert!(events.history.is_empty());
+    /// # }
+    /// ```
+    pub fn get_events(&self) -> Result<InstanceEvents> {
+        let values = self.get_many(&[SCHEDULED_EVENTS, EVENT_HISTORY, REBALANCE_RECOMMENDATION])?;
+        let optional = |path: &str| missing_as_none(values[path].clone());
+        let events = |path: &str| -> Result<Vec<InstanceEvent>> {
+            match optional(path)? {
+                Some(body) if !body.trim().is_empty() => parse_events(&body),
+                _ => Ok(Vec::new()),
+            }
+        };
+
+        let rebalance_recommended_at = match optional(REBALANCE_RECOMMENDATION)? {
+            Some(body) => {
+                let notice_time = required_string_field(&body, "noticeTime")?;
+                let time = parse_rfc3339(&notice_time)
+                    .ok_or_else(|| Error::InvalidValue(format!("noticeTime: {:?}", notice_time)))?;
+                Some(time)
+            }
+            None => None,
+        };
+
+        Ok(InstanceEvents {
+            scheduled: events(SCHEDULED_EVENTS)?,
+            history: events(EVENT_HISTORY)?,
+            rebalance_recommended_at,
+        })
+    }
+}
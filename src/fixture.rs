@@ -0,0 +1,216 @@
+//! Recording real IMDS responses to a JSON fixture, and replaying them
+//! later without IMDS.
+//!
+//! `RecordingTransport` wraps another transport and keeps every metadata
+//! response that goes through it. The resulting `Fixture` can be saved,
+//! attached to a bug report, and served back by `ReplayTransport` for
+//! deterministic tests. Credentials are redacted as they're recorded.
+//!
+//! # Examples:
+//! ```no_run
+//! use ec2_instance_metadata::fixture::{RecordingTransport, ReplayTransport};
+//! use ec2_instance_metadata::InstanceMetadataClient;
+//!
+//! // On an instance:
+//! let recorder = RecordingTransport::default();
+//! let client = InstanceMetadataClient::builder()
+//!     .transport(recorder.clone())
+//!     .build();
+//! client.get().unwrap();
+//! recorder.fixture().save("instance.json").unwrap();
+//!
+//! // Anywhere else:
+//! let client = InstanceMetadataClient::builder()
+//!     .transport(ReplayTransport::from_file("instance.json").unwrap())
+//!     .build();
+//! println!("{}", client.get().unwrap());
+//! ```
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::transport::{self, ImdsTransport, Method, Request, Response, TransportConfig};
+use crate::Result;
+
+// Fields of the credential documents which are replaced when recording.
+const SECRET_FIELDS: &[&str] = &["SecretAccessKey", "Token"];
+const REDACTED: &str = "REDACTED";
+
+/// A recorded response.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FixtureResponse {
+    pub status: u16,
+    pub body: String,
+}
+
+/// `Fixture` holds recorded GET responses, keyed by URL path, e.g.
+/// `/latest/meta-data/instance-id`. Token requests aren't recorded;
+/// `ReplayTransport` answers them itself.
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct Fixture {
+    pub responses: BTreeMap<String, FixtureResponse>,
+}
+
+impl Fixture {
+    /// Read a fixture saved with `save()`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        Ok(serde_json::from_slice(&fs::read(path)?)?)
+    }
+
+    /// Write the fixture as pretty-printed JSON.
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        Ok(fs::write(path, serde_json::to_string_pretty(self)?)?)
+    }
+}
+
+// The path and query of a URL; the scheme and host are whatever endpoint
+// the client was configured with.
+fn url_path(url: &str) -> &str {
+    match url.split_once("://") {
+        Some((_, rest)) => rest.find('/').map_or("/", |i| &rest[i..]),
+        None => url,
+    }
+}
+
+// Replaces secrets in credential documents, leaving everything else (and
+// bodies which aren't JSON objects) as is.
+fn redact(path: &str, body: String) -> String {
+    if !path.contains("security-credentials/") {
+        return body;
+    }
+
+    let mut document: Value = match serde_json::from_str(&body) {
+        Ok(Value::Object(document)) => Value::Object(document),
+        _ => return body,
+    };
+    for field in SECRET_FIELDS {
+        if document.get(field).is_some() {
+            document[*field] = Value::String(REDACTED.to_string());
+        }
+    }
+    document.to_string()
+}
+
+fn lock(fixture: &Mutex<Fixture>) -> MutexGuard<'_, Fixture> {
+    fixture.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// `RecordingTransport` passes requests through to another transport and
+/// records the responses. Clones share the same recording, so keep one
+/// to read it back after handing the other to the builder.
+#[derive(Debug, Clone)]
+pub struct RecordingTransport {
+    inner: Arc<dyn ImdsTransport>,
+    fixture: Arc<Mutex<Fixture>>,
+}
+
+impl RecordingTransport {
+    pub fn new(inner: impl ImdsTransport + 'static) -> Self {
+        Self {
+            inner: Arc::new(inner),
+            fixture: Arc::default(),
+        }
+    }
+
+    /// Everything recorded so far.
+    pub fn fixture(&self) -> Fixture {
+        lock(&self.fixture).clone()
+    }
+}
+
+/// Records through the default HTTP backend.
+impl Default for RecordingTransport {
+    fn default() -> Self {
+        Self {
+            inner: transport::default_transport(&TransportConfig::default()).into(),
+            fixture: Arc::default(),
+        }
+    }
+}
+
+impl ImdsTransport for RecordingTransport {
+    fn send(&self, request: &Request) -> Result<Response> {
+        let resp = self.inner.send(request)?;
+
+        // A 401 only means the token expired, and the client retries with
+        // a new one, so it's not worth keeping.
+        if request.method == Method::Get && resp.status != 401 {
+            let path = url_path(&request.url);
+            let body = String::from_utf8_lossy(&resp.body).into_owned();
+            let recorded = FixtureResponse {
+                status: resp.status,
+                body: redact(path, body),
+            };
+            lock(&self.fixture)
+                .responses
+                .insert(path.to_string(), recorded);
+        }
+
+        Ok(resp)
+    }
+}
+
+/// `ReplayTransport` answers requests from a `Fixture`. Token requests
+/// always succeed, and paths that weren't recorded get a 404.
+///
+/// # Examples:
+/// ```
+/// # #[cfg(feature = "mock")] {
+/// use ec2_instance_metadata::fixture::{RecordingTransport, ReplayTransport};
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// use ec2_instance_metadata::InstanceMetadataClient;
+///
+/// let server = MockImdsServer::start().unwrap();
+/// let recorder = RecordingTransport::new(server.transport());
+/// let recorded = InstanceMetadataClient::builder()
+///     .transport(recorder.clone())
+///     .build()
+///     .get()
+///     .unwrap();
+/// drop(server);
+///
+/// let replayed = InstanceMetadataClient::builder()
+///     .transport(ReplayTransport::new(recorder.fixture()))
+///     .build()
+///     .get()
+///     .unwrap();
+/// assert_eq!(recorded, replayed);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct ReplayTransport {
+    fixture: Fixture,
+}
+
+impl ReplayTransport {
+    pub fn new(fixture: Fixture) -> Self {
+        Self { fixture }
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self> {
+        Fixture::load(path).map(Self::new)
+    }
+}
+
+impl ImdsTransport for ReplayTransport {
+    fn send(&self, request: &Request) -> Result<Response> {
+        let (status, body) = match request.method {
+            Method::Put => (200, "replay-token".to_string()),
+            Method::Get => match self.fixture.responses.get(url_path(&request.url)) {
+                Some(recorded) => (recorded.status, recorded.body.clone()),
+                None => (404, String::new()),
+            },
+        };
+
+        Ok(Response {
+            status,
+            headers: vec![("Content-Type".into(), "text/plain".into())],
+            body: body.into_bytes(),
+        })
+    }
+}
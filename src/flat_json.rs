@@ -0,0 +1,284 @@
+//! Just enough JSON to read string fields out of the flat documents IMDS
+//! serves, such as the identity credentials and Spot notices, so the core
+//! client works without the `json` feature and its serde dependencies.
+//!
+//! The parser is private, so it's exercised through the Spot interruption
+//! notice, whose `action` and `time` fields are read with it.
+//!
+//! # Examples:
+//! Escapes, including `\u` escapes and surrogate pairs, are decoded, and
+//! nested objects and arrays are skipped over:
+//! ```
+//! # #[cfg(feature = "mock")] {
+//! use ec2_instance_metadata::mock::MockImdsServer;
+//! let server = MockImdsServer::start().unwrap();
+//! let action = |body: &str| {
+//!     server.set("meta-data/spot/instance-action", body);
+//!     server.client().get_spot_instance_action()
+//! };
+//!
+//! let notice = action(r#"{"action": "st\"op\\", "time": "2024\/01\/01\tT"}"#).unwrap().unwrap();
+//! assert_eq!(notice.action, "st\"op\\");
+//! assert_eq!(notice.time, "2024/01/01\tT");
+//!
+//! let notice = action(r#"{"action": "\u0073top \u00e9 \ud83d\ude00", "time": "t"}"#).unwrap().unwrap();
+//! assert_eq!(notice.action, "stop \u{e9} \u{1f600}");
+//!
+//! let nested = r#"{
+//!     "extra": {"action": "nested", "list": [1, {"time": "]}"}, true, null]},
+//!     "count": -1.5e3,
+//!     "action": "terminate",
+//!     "time": "2024-01-01T00:00:00Z"
+//! }"#;
+//! let notice = action(nested).unwrap().unwrap();
+//! assert_eq!(notice.action, "terminate");
+//! assert_eq!(notice.time, "2024-01-01T00:00:00Z");
+//! # }
+//! ```
+//!
+//! Missing and non-string fields, and malformed documents, are
+//! `Error::JsonError`s:
+//! ```
+//! # #[cfg(feature = "mock")] {
+//! use ec2_instance_metadata::mock::MockImdsServer;
+//! use ec2_instance_metadata::Error;
+//! let server = MockImdsServer::start().unwrap();
+//! let action = |body: &str| {
+//!     server.set("meta-data/spot/instance-action", body);
+//!     server.client().get_spot_instance_action()
+//! };
+//! let json_error = |body: &str| match action(body) {
+//!     Err(Error::JsonError(message)) => message,
+//!     result => panic!("{:?} parsed as {:?}", body, result),
+//! };
+//!
+//! assert_eq!(json_error(r#"{"action": "stop"}"#), "Missing time field");
+//! assert_eq!(json_error(r#"{"action": "stop", "time": 5}"#), "Missing time field");
+//! assert!(json_error(r#"{"action": "stop", "time": "t"#).contains("unterminated string"));
+//! assert!(json_error(r#"{"action": "\q", "time": "t"}"#).contains("invalid escape"));
+//! assert!(json_error(r#"{"action": "\u12", "time": "t"}"#).contains(r"invalid \u escape"));
+//! assert!(json_error(r#"{"action": "\ud83d", "time": "t"}"#).contains("unpaired surrogate"));
+//! assert!(json_error(r#"{"action": {"a": [1}"#).contains("unterminated value"));
+//! assert!(json_error(r#"["action", "stop"]"#).contains("expected '{'"));
+//! assert!(json_error(r#"{"action" "stop"}"#).contains("expected ':'"));
+//! # }
+//! ```
+
+use crate::{Error, Result};
+
+struct Parser<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn error(&self, what: &str) -> Error {
+        Error::JsonError(format!("{} at byte {}", what, self.pos))
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn eat(&mut self, byte: u8) -> bool {
+        self.skip_whitespace();
+        if self.peek() == Some(byte) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<()> {
+        if self.eat(byte) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("expected '{}'", byte as char)))
+        }
+    }
+
+    fn hex4(&mut self) -> Result<u32> {
+        let digits = self
+            .bytes
+            .get(self.pos..self.pos + 4)
+            .and_then(|digits| std::str::from_utf8(digits).ok())
+            .and_then(|digits| u32::from_str_radix(digits, 16).ok())
+            .ok_or_else(|| self.error("invalid \\u escape"))?;
+        self.pos += 4;
+        Ok(digits)
+    }
+
+    fn string(&mut self) -> Result<String> {
+        self.expect(b'"')?;
+        let mut out = Vec::new();
+
+        loop {
+            let byte = self
+                .peek()
+                .ok_or_else(|| self.error("unterminated string"))?;
+            self.pos += 1;
+            match byte {
+                b'"' => break,
+                b'\\' => {
+                    let escape = self
+                        .peek()
+                        .ok_or_else(|| self.error("unterminated string"))?;
+                    self.pos += 1;
+                    let c = match escape {
+                        b'"' => '"',
+                        b'\\' => '\\',
+                        b'/' => '/',
+                        b'b' => '\u{8}',
+                        b'f' => '\u{c}',
+                        b'n' => '\n',
+                        b'r' => '\r',
+                        b't' => '\t',
+                        b'u' => self.unicode_escape()?,
+                        _ => return Err(self.error("invalid escape")),
+                    };
+                    out.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes());
+                }
+                byte => out.push(byte),
+            }
+        }
+
+        String::from_utf8(out).map_err(|_| self.error("invalid UTF-8"))
+    }
+
+    // Characters outside the BMP are escaped as a surrogate pair.
+    fn unicode_escape(&mut self) -> Result<char> {
+        let high = self.hex4()?;
+        let code = if (0xd800..0xdc00).contains(&high) {
+            if self.bytes.get(self.pos..self.pos + 2) != Some(b"\\u") {
+                return Err(self.error("unpaired surrogate"));
+            }
+            self.pos += 2;
+            let low = self.hex4()?;
+            if !(0xdc00..0xe000).contains(&low) {
+                return Err(self.error("unpaired surrogate"));
+            }
+            0x10000 + ((high - 0xd800) << 10) + (low - 0xdc00)
+        } else {
+            high
+        };
+        char::from_u32(code).ok_or_else(|| self.error("invalid \\u escape"))
+    }
+
+    // Skips a value of any type, including nested objects and arrays.
+    fn skip_value(&mut self) -> Result<()> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some(b'"') => self.string().map(drop),
+            Some(b'{') | Some(b'[') => {
+                let mut depth = 0;
+                loop {
+                    match self.peek() {
+                        Some(b'"') => {
+                            self.string()?;
+                            continue;
+                        }
+                        Some(b'{') | Some(b'[') => depth += 1,
+                        Some(b'}') | Some(b']') => depth -= 1,
+                        Some(_) => {}
+                        None => return Err(self.error("unterminated value")),
+                    }
+                    self.pos += 1;
+                    if depth == 0 {
+                        return Ok(());
+                    }
+                }
+            }
+            _ => {
+                let start = self.pos;
+                while let Some(byte) = self.peek() {
+                    if matches!(byte, b',' | b'}' | b']') || byte.is_ascii_whitespace() {
+                        break;
+                    }
+                    self.pos += 1;
+                }
+                if self.pos == start {
+                    Err(self.error("expected a value"))
+                } else {
+                    Ok(())
+                }
+            }
+        }
+    }
+}
+
+/// The value of the string field `name` in the JSON object `document`, or
+/// `None` if the object has no such field or it isn't a string.
+pub(crate) fn string_field(document: &str, name: &str) -> Result<Option<String>> {
+    let mut parser = Parser {
+        bytes: document.as_bytes(),
+        pos: 0,
+    };
+    let mut found = None;
+
+    parser.expect(b'{')?;
+    if parser.eat(b'}') {
+        return Ok(None);
+    }
+    loop {
+        let key = parser.string()?;
+        parser.expect(b':')?;
+        parser.skip_whitespace();
+        if parser.peek() == Some(b'"') {
+            let value = parser.string()?;
+            if found.is_none() && key == name {
+                found = Some(value);
+            }
+        } else {
+            parser.skip_value()?;
+        }
+
+        if parser.eat(b',') {
+            parser.skip_whitespace();
+            continue;
+        }
+        parser.expect(b'}')?;
+        return Ok(found);
+    }
+}
+
+/// The objects in the JSON array `document`, as slices of it to read with
+/// `string_field`.
+pub(crate) fn objects(document: &str) -> Result<Vec<&str>> {
+    let mut parser = Parser {
+        bytes: document.as_bytes(),
+        pos: 0,
+    };
+    let mut objects = Vec::new();
+
+    parser.expect(b'[')?;
+    if parser.eat(b']') {
+        return Ok(objects);
+    }
+    loop {
+        parser.skip_whitespace();
+        if parser.peek() != Some(b'{') {
+            return Err(parser.error("expected an object"));
+        }
+        let start = parser.pos;
+        parser.skip_value()?;
+        objects.push(&document[start..parser.pos]);
+
+        if parser.eat(b',') {
+            continue;
+        }
+        parser.expect(b']')?;
+        return Ok(objects);
+    }
+}
+
+/// Like `string_field`, but a missing field is an error.
+pub(crate) fn required_string_field(document: &str, name: &str) -> Result<String> {
+    string_field(document, name)?.ok_or_else(|| Error::JsonError(format!("Missing {} field", name)))
+}
@@ -0,0 +1,122 @@
+//! Reading user-defined structs from arbitrary metadata paths, usually
+//! through `#[derive(FromImds)]` with the `derive` feature.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::{
+    AccountId, AmiId, AvailabilityZone, Error, InstanceId, InstanceMetadataClient, InstanceType,
+    Region, Result,
+};
+
+/// `FromImds` types can be read from IMDS with
+/// `InstanceMetadataClient::fetch_into`.
+///
+/// With the `derive` feature it can be derived for structs whose fields
+/// each name a path, relative to `/latest/`, and have an `ImdsValue` type.
+/// `Option` fields are `None` when IMDS doesn't serve the path, and `Vec`
+/// fields hold one entry per line.
+///
+/// # Examples:
+/// ```
+/// # #[cfg(all(feature = "mock", feature = "derive"))] {
+/// use std::net::Ipv4Addr;
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// use ec2_instance_metadata::FromImds;
+///
+/// #[derive(FromImds)]
+/// struct Network {
+///     #[imds(path = "meta-data/mac")]
+///     mac: String,
+///     #[imds(path = "meta-data/local-ipv4")]
+///     local_ipv4: Ipv4Addr,
+///     #[imds(path = "meta-data/public-ipv4")]
+///     public_ipv4: Option<Ipv4Addr>,
+/// }
+///
+/// let server = MockImdsServer::start().unwrap();
+/// server.set("meta-data/mac", "0e:49:61:0f:c3:11");
+/// server.set("meta-data/local-ipv4", "172.31.1.2");
+///
+/// let network: Network = server.client().fetch_into().unwrap();
+/// assert_eq!(network.local_ipv4, Ipv4Addr::new(172, 31, 1, 2));
+/// assert_eq!(network.public_ipv4, None);
+/// # }
+/// ```
+pub trait FromImds: Sized {
+    fn from_imds(client: &InstanceMetadataClient) -> Result<Self>;
+}
+
+/// `ImdsValue` converts the response for a single metadata path into a
+/// field of a `FromImds` struct.
+pub trait ImdsValue: Sized {
+    /// `value` is the result of fetching `path`.
+    fn from_imds_value(value: Result<String>, path: &str) -> Result<Self>;
+}
+
+impl ImdsValue for String {
+    fn from_imds_value(value: Result<String>, _path: &str) -> Result<Self> {
+        value
+    }
+}
+
+impl<T: ImdsValue> ImdsValue for Option<T> {
+    fn from_imds_value(value: Result<String>, path: &str) -> Result<Self> {
+        match value {
+            Err(Error::NotFound(_)) => Ok(None),
+            value => T::from_imds_value(value, path).map(Some),
+        }
+    }
+}
+
+impl<T: ImdsValue> ImdsValue for Vec<T> {
+    fn from_imds_value(value: Result<String>, path: &str) -> Result<Self> {
+        value?
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty())
+            .map(|line| T::from_imds_value(Ok(line.to_string()), path))
+            .collect()
+    }
+}
+
+macro_rules! impl_imds_value_from_str {
+    ($($ty:ty),*) => {
+        $(
+            impl ImdsValue for $ty {
+                fn from_imds_value(value: Result<String>, path: &str) -> Result<Self> {
+                    let value = value?;
+                    value
+                        .trim()
+                        .parse()
+                        .map_err(|_| Error::InvalidValue(format!("{} {:?}", path, value)))
+                }
+            }
+        )*
+    };
+}
+
+impl_imds_value_from_str!(
+    bool,
+    u16,
+    u32,
+    u64,
+    i32,
+    i64,
+    IpAddr,
+    Ipv4Addr,
+    Ipv6Addr,
+    InstanceType,
+    Region,
+    InstanceId,
+    AmiId,
+    AccountId,
+    AvailabilityZone
+);
+
+impl InstanceMetadataClient {
+    /// Read a `FromImds` type, such as a struct with `#[derive(FromImds)]`.
+    /// The paths it reads share the client's token and cache.
+    pub fn fetch_into<T: FromImds>(&self) -> Result<T> {
+        T::from_imds(self)
+    }
+}
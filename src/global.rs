@@ -0,0 +1,34 @@
+use std::sync::OnceLock;
+
+use crate::{InstanceMetadata, InstanceMetadataClient, Result};
+
+static CLIENT: OnceLock<InstanceMetadataClient> = OnceLock::new();
+static METADATA: OnceLock<InstanceMetadata> = OnceLock::new();
+
+/// Get the process-wide instance metadata, fetching it on first use.
+///
+/// Threads that call this concurrently before the first fetch completes
+/// share that one fetch. A failed fetch isn't remembered, so a later call
+/// tries again.
+///
+/// # Examples:
+/// ```no_run
+/// let metadata = ec2_instance_metadata::try_global().expect("not on EC2");
+/// println!("running in {}", metadata.region);
+/// ```
+pub fn try_global() -> Result<&'static InstanceMetadata> {
+    if let Some(metadata) = METADATA.get() {
+        return Ok(metadata);
+    }
+
+    let metadata = CLIENT.get_or_init(InstanceMetadataClient::new).get()?;
+    Ok(METADATA.get_or_init(|| metadata))
+}
+
+/// Like `try_global()`, but panics if the metadata can't be fetched.
+pub fn global() -> &'static InstanceMetadata {
+    match try_global() {
+        Ok(metadata) => metadata,
+        Err(e) => panic!("failed to fetch EC2 instance metadata: {}", e),
+    }
+}
@@ -0,0 +1,129 @@
+//! Validated identifiers. IMDS answers in plain text, so a truncated or
+//! garbled response would otherwise only show up wherever the id is used.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "json")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Error, Result};
+
+// `prefix` followed by 8 to 17 lowercase hex digits, the two lengths EC2
+// has issued.
+fn is_hex_id(s: &str, prefix: &str) -> bool {
+    s.strip_prefix(prefix).is_some_and(|hex| {
+        (8..=17).contains(&hex.len())
+            && hex
+                .bytes()
+                .all(|b| b.is_ascii_digit() || (b'a'..=b'f').contains(&b))
+    })
+}
+
+macro_rules! id_type {
+    ($(#[$doc:meta])* $name:ident, $what:literal, $valid:expr) => {
+        $(#[$doc])*
+        #[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+        pub struct $name(String);
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl FromStr for $name {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self> {
+                let valid: fn(&str) -> bool = $valid;
+                if valid(s) {
+                    Ok($name(s.to_string()))
+                } else {
+                    Err(Error::InvalidValue(format!(concat!($what, " {:?}"), s)))
+                }
+            }
+        }
+
+        impl AsRef<str> for $name {
+            fn as_ref(&self) -> &str {
+                &self.0
+            }
+        }
+
+        impl PartialEq<str> for $name {
+            fn eq(&self, other: &str) -> bool {
+                self.0 == other
+            }
+        }
+
+        impl PartialEq<&str> for $name {
+            fn eq(&self, other: &&str) -> bool {
+                self.0 == *other
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(id: $name) -> String {
+                id.0
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+
+        #[cfg(feature = "json")]
+        impl Serialize for $name {
+            fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+                serializer.serialize_str(&self.0)
+            }
+        }
+
+        #[cfg(feature = "json")]
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+                let s = String::deserialize(deserializer)?;
+                s.parse().map_err(serde::de::Error::custom)
+            }
+        }
+    };
+}
+
+id_type!(
+    /// An EC2 instance id, e.g. `i-0123456789abcdef0`.
+    ///
+    /// # Examples:
+    /// ```
+    /// use ec2_instance_metadata::InstanceId;
+    /// assert!("i-0123456789abcdef0".parse::<InstanceId>().is_ok());
+    /// assert!("i-0123456789abc\u{0}".parse::<InstanceId>().is_err());
+    /// ```
+    InstanceId,
+    "instance id",
+    |s| is_hex_id(s, "i-")
+);
+
+id_type!(
+    /// An AMI id, e.g. `ami-0123456789abcdef0`.
+    AmiId,
+    "AMI id",
+    |s| is_hex_id(s, "ami-")
+);
+
+id_type!(
+    /// A 12-digit AWS account id. Leading zeros are significant, so it's
+    /// kept as a string.
+    AccountId,
+    "account id",
+    |s| s.len() == 12 && s.bytes().all(|b| b.is_ascii_digit())
+);
+
+impl InstanceId {
+    // For lenient mode, where simulators can serve anything.
+    pub(crate) fn unchecked(s: String) -> Self {
+        InstanceId(s)
+    }
+}
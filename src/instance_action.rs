@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+#[cfg(feature = "json")]
+use serde::{Serialize, Serializer};
+
+use crate::{Error, InstanceMetadataClient, Result, Watcher};
+
+const INSTANCE_ACTION: &str = "meta-data/instance-action";
+const HIBERNATION_CONFIGURED: &str = "meta-data/hibernation/configured";
+
+/// `InstanceAction` is an action requested for the instance, from
+/// `meta-data/instance-action`. IMDS documents `none`, `shutdown` and
+/// `bundle-pending`; anything else is kept as `Other`.
+///
+/// # Examples:
+/// ```
+/// use ec2_instance_metadata::InstanceAction;
+/// assert_eq!(InstanceAction::from("none"), InstanceAction::None);
+/// assert_eq!(InstanceAction::from(""), InstanceAction::None);
+/// assert_eq!(InstanceAction::from("shutdown\n"), InstanceAction::Shutdown);
+/// assert_eq!(InstanceAction::from("bundle-pending"), InstanceAction::BundlePending);
+/// assert_eq!(InstanceAction::from("reboot"), InstanceAction::Other("reboot".into()));
+///
+/// assert!(InstanceAction::Shutdown.is_shutdown());
+/// assert!(InstanceAction::BundlePending.is_pending());
+/// assert!(!InstanceAction::BundlePending.is_shutdown());
+/// assert!(!InstanceAction::None.is_pending());
+/// assert_eq!(InstanceAction::BundlePending.to_string(), "bundle-pending");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum InstanceAction {
+    /// No action is pending
+    None,
+    /// The instance is about to be shut down
+    Shutdown,
+    /// The instance should prepare to be bundled into an AMI (instance
+    /// store-backed instances only)
+    BundlePending,
+    /// A value this crate doesn't know about yet
+    Other(String),
+}
+
+impl InstanceAction {
+    pub fn as_str(&self) -> &str {
+        match self {
+            InstanceAction::None => "none",
+            InstanceAction::Shutdown => "shutdown",
+            InstanceAction::BundlePending => "bundle-pending",
+            InstanceAction::Other(s) => s,
+        }
+    }
+
+    /// True if any action is pending, including bundling.
+    pub fn is_pending(&self) -> bool {
+        *self != InstanceAction::None
+    }
+
+    /// True if the instance is about to be shut down. Bundling isn't a
+    /// shutdown.
+    pub fn is_shutdown(&self) -> bool {
+        *self == InstanceAction::Shutdown
+    }
+}
+
+impl From<&str> for InstanceAction {
+    fn from(s: &str) -> Self {
+        match s.trim() {
+            "none" | "" => InstanceAction::None,
+            "shutdown" => InstanceAction::Shutdown,
+            "bundle-pending" => InstanceAction::BundlePending,
+            other => InstanceAction::Other(other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for InstanceAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for InstanceAction {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl InstanceMetadataClient {
+    /// Get the action requested for the instance, if any.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::InstanceAction;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let client = server.client();
+    /// assert_eq!(client.get_instance_action().unwrap(), InstanceAction::None);
+    ///
+    /// server.set("meta-data/instance-action", "bundle-pending");
+    /// assert_eq!(client.get_instance_action().unwrap(), InstanceAction::BundlePending);
+    /// # }
+    /// ```
+    pub fn get_instance_action(&self) -> Result<InstanceAction> {
+        match self.fetch_path(INSTANCE_ACTION) {
+            Ok(action) => Ok(InstanceAction::from(action.as_str())),
+            Err(Error::NotFound(_)) => Ok(InstanceAction::None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Start a `Watcher` which polls for a requested instance action every
+    /// `interval`.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use std::time::Duration;
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::{InstanceAction, WatchEvent};
+    /// let server = MockImdsServer::start().unwrap();
+    /// let watcher = server
+    ///     .client()
+    ///     .watch_instance_action(Duration::from_millis(50))
+    ///     .unwrap();
+    /// assert_eq!(*watcher.current(), InstanceAction::None);
+    ///
+    /// server.set("meta-data/instance-action", "shutdown");
+    /// match watcher.events().recv_timeout(Duration::from_secs(5)).unwrap() {
+    ///     WatchEvent::Changed { previous, current } => {
+    ///         assert_eq!(*previous, InstanceAction::None);
+    ///         assert_eq!(*current, InstanceAction::Shutdown);
+    ///     }
+    ///     event => panic!("unexpected {:?}", event),
+    /// }
+    /// # }
+    /// ```
+    pub fn watch_instance_action(self, interval: Duration) -> Result<Watcher<InstanceAction>> {
+        Watcher::spawn(interval, move || self.get_instance_action())
+    }
+
+    /// Get whether the instance was launched with hibernation enabled.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::Error;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let client = server.client();
+    /// let error = client.get_hibernation_configured().unwrap_err();
+    /// assert!(matches!(error, Error::NotFound(_)), "{:?}", error);
+    ///
+    /// server.set("meta-data/hibernation/configured", "true");
+    /// assert!(client.get_hibernation_configured().unwrap());
+    ///
+    /// server.set("meta-data/hibernation/configured", "false");
+    /// assert!(!client.get_hibernation_configured().unwrap());
+    /// # }
+    /// ```
+    pub fn get_hibernation_configured(&self) -> Result<bool> {
+        let configured = self.get_path(HIBERNATION_CONFIGURED)?;
+        match configured.trim() {
+            "true" => Ok(true),
+            "false" => Ok(false),
+            other => Err(Error::InvalidValue(format!(
+                "{}: {:?}",
+                HIBERNATION_CONFIGURED, other
+            ))),
+        }
+    }
+}
@@ -0,0 +1,177 @@
+use std::str::FromStr;
+
+#[cfg(feature = "json")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Error, Result};
+
+// Series which are GPU-backed, and the wider set with any accelerator
+// (GPUs, Trainium/Inferentia, FPGAs, video transcoders).
+const GPU_SERIES: &[&str] = &["p", "g", "gr"];
+const ACCELERATED_SERIES: &[&str] = &["p", "g", "gr", "dl", "trn", "inf", "f", "vt"];
+
+/// `InstanceType` is a parsed EC2 instance type name such as
+/// `c7g.2xlarge`: the family (`c7g`), made of a series (`c`), generation
+/// (`7`), and attribute suffixes (`g`), followed by a size (`2xlarge`).
+///
+/// # Examples:
+/// ```
+/// use ec2_instance_metadata::InstanceType;
+/// let instance_type: InstanceType = "c7g.2xlarge".parse().unwrap();
+/// assert_eq!(instance_type.family(), "c7g");
+/// assert_eq!(instance_type.series(), "c");
+/// assert_eq!(instance_type.generation(), Some(7));
+/// assert_eq!(instance_type.attributes(), "g");
+/// assert_eq!(instance_type.size(), "2xlarge");
+/// assert!(instance_type.is_graviton());
+/// assert!(!instance_type.is_gpu());
+///
+/// let instance_type: InstanceType = "g5.xlarge".parse().unwrap();
+/// assert!(instance_type.is_gpu());
+/// assert!(!instance_type.is_graviton());
+///
+/// let instance_type: InstanceType = "a1.medium".parse().unwrap();
+/// assert!(instance_type.is_graviton());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct InstanceType {
+    name: String,
+    // Byte offsets into `name` marking the end of the series, generation,
+    // and family, in that order.
+    series_end: usize,
+    generation_end: usize,
+    family_end: usize,
+}
+
+impl InstanceType {
+    /// The full name, e.g. `c7g.2xlarge`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The family, e.g. `c7g`.
+    pub fn family(&self) -> &str {
+        &self.name[..self.family_end]
+    }
+
+    /// The series letters, e.g. `c` for `c7g` or `trn` for `trn1n`.
+    pub fn series(&self) -> &str {
+        &self.name[..self.series_end]
+    }
+
+    /// The generation number, e.g. `7` for `c7g`. `None` for families
+    /// without one, such as `u-6tb1`.
+    pub fn generation(&self) -> Option<u32> {
+        self.name[self.series_end..self.generation_end].parse().ok()
+    }
+
+    /// The attribute suffixes after the generation, e.g. `gd` for `m6gd`
+    /// or `-6tb1` for `u-6tb1`.
+    pub fn attributes(&self) -> &str {
+        &self.name[self.generation_end..self.family_end]
+    }
+
+    /// The size, e.g. `2xlarge` or `metal-48xl`.
+    pub fn size(&self) -> &str {
+        &self.name[self.family_end + 1..]
+    }
+
+    /// True for AWS Graviton (Arm) families, marked by a `g` attribute,
+    /// and for `a1`, the first-generation Graviton family, which has none.
+    pub fn is_graviton(&self) -> bool {
+        self.family() == "a1" || self.attributes().contains('g')
+    }
+
+    /// True for AMD processor families, marked by an `a` attribute.
+    pub fn is_amd(&self) -> bool {
+        self.attributes().contains('a')
+    }
+
+    /// True for families with local NVMe instance storage, marked by a `d`
+    /// attribute.
+    pub fn has_local_storage(&self) -> bool {
+        self.attributes().contains('d')
+    }
+
+    /// True for GPU families (`p`, `g`, `gr`).
+    pub fn is_gpu(&self) -> bool {
+        GPU_SERIES.contains(&self.series())
+    }
+
+    /// True for any accelerated computing family: GPUs, Trainium,
+    /// Inferentia, FPGAs, and video transcoding.
+    pub fn is_accelerated(&self) -> bool {
+        ACCELERATED_SERIES.contains(&self.series())
+    }
+
+    /// True for bare metal sizes.
+    pub fn is_bare_metal(&self) -> bool {
+        self.size().starts_with("metal")
+    }
+}
+
+impl FromStr for InstanceType {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let name = s.trim();
+        let family_end = match name.find('.') {
+            Some(i) if i > 0 && i + 1 < name.len() => i,
+            _ => return Err(Error::InvalidValue(format!("instance type {:?}", s))),
+        };
+
+        let family = &name[..family_end];
+        let series_end = family
+            .find(|c: char| !c.is_ascii_lowercase())
+            .unwrap_or(family_end);
+        let generation_end = family[series_end..]
+            .find(|c: char| !c.is_ascii_digit())
+            .map_or(family_end, |i| series_end + i);
+
+        Ok(InstanceType {
+            name: name.to_string(),
+            series_end,
+            generation_end,
+            family_end,
+        })
+    }
+}
+
+impl AsRef<str> for InstanceType {
+    fn as_ref(&self) -> &str {
+        &self.name
+    }
+}
+
+impl PartialEq<str> for InstanceType {
+    fn eq(&self, other: &str) -> bool {
+        self.name == other
+    }
+}
+
+impl PartialEq<&str> for InstanceType {
+    fn eq(&self, other: &&str) -> bool {
+        self.name == *other
+    }
+}
+
+impl std::fmt::Display for InstanceType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name)
+    }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for InstanceType {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.name)
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'de> Deserialize<'de> for InstanceType {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
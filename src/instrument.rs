@@ -0,0 +1,106 @@
+//! Optional `tracing` instrumentation of IMDS requests, enabled by the
+//! `tracing` feature. Without the feature these helpers compile down to a
+//! plain call.
+//!
+//! # Examples:
+//! ```
+//! # #[cfg(all(feature = "tracing", feature = "mock"))] {
+//! use std::fmt::Debug;
+//! use std::sync::{Arc, Mutex};
+//! use ec2_instance_metadata::mock::MockImdsServer;
+//! use tracing::field::{Field, Visit};
+//! use tracing::span::{Attributes, Id, Record};
+//! use tracing::{Event, Metadata, Subscriber};
+//!
+//! // Notes the name and path of each span.
+//! struct Spans(Arc<Mutex<Vec<String>>>);
+//!
+//! struct Path(String);
+//!
+//! impl Visit for Path {
+//!     fn record_str(&mut self, field: &Field, value: &str) {
+//!         if field.name() == "path" {
+//!             self.0 = value.to_string();
+//!         }
+//!     }
+//!     fn record_debug(&mut self, _field: &Field, _value: &dyn Debug) {}
+//! }
+//!
+//! impl Subscriber for Spans {
+//!     fn enabled(&self, _metadata: &Metadata) -> bool {
+//!         true
+//!     }
+//!     fn new_span(&self, span: &Attributes) -> Id {
+//!         let mut path = Path(String::new());
+//!         span.record(&mut path);
+//!         let mut spans = self.0.lock().unwrap();
+//!         spans.push(format!("{} {}", span.metadata().name(), path.0));
+//!         Id::from_u64(spans.len() as u64)
+//!     }
+//!     fn record(&self, _span: &Id, _values: &Record) {}
+//!     fn record_follows_from(&self, _span: &Id, _follows: &Id) {}
+//!     fn event(&self, _event: &Event) {}
+//!     fn enter(&self, _span: &Id) {}
+//!     fn exit(&self, _span: &Id) {}
+//! }
+//!
+//! let spans = Arc::new(Mutex::new(Vec::new()));
+//! let server = MockImdsServer::start().unwrap();
+//! let client = server.client();
+//! tracing::subscriber::with_default(Spans(Arc::clone(&spans)), || {
+//!     client.get_path("meta-data/instance-id").unwrap();
+//! });
+//! assert_eq!(
+//!     *spans.lock().unwrap(),
+//!     ["imds_request api/token", "imds_request meta-data/instance-id"]
+//! );
+//! # }
+//! ```
+
+use crate::transport::{HasStatus, Request};
+use crate::Result;
+
+/// Runs `send` inside an `imds_request` span recording the request path,
+/// response status, latency, and retry count.
+#[cfg(feature = "tracing")]
+pub(crate) fn traced<R, F>(request: &Request, path: &str, retries: u32, send: F) -> Result<R>
+where
+    R: HasStatus,
+    F: FnOnce() -> Result<R>,
+{
+    use std::time::Instant;
+    use tracing::field::Empty;
+
+    let span = tracing::debug_span!(
+        "imds_request",
+        method = request.method.as_str(),
+        path,
+        retries,
+        status = Empty,
+        latency_ms = Empty,
+    );
+    let _entered = span.enter();
+
+    let start = Instant::now();
+    let result = send();
+    span.record("latency_ms", start.elapsed().as_millis() as u64);
+
+    match &result {
+        Ok(resp) => {
+            span.record("status", resp.status());
+            tracing::debug!(status = resp.status(), "imds response");
+        }
+        Err(e) => tracing::warn!(error = %e, "imds request failed"),
+    }
+
+    result
+}
+
+#[cfg(not(feature = "tracing"))]
+pub(crate) fn traced<R, F>(_request: &Request, _path: &str, _retries: u32, send: F) -> Result<R>
+where
+    R: HasStatus,
+    F: FnOnce() -> Result<R>,
+{
+    send()
+}
@@ -1,82 +1,173 @@
-use serde_json::Value;
-extern crate ureq;
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+#[cfg(feature = "json")]
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+mod account;
+mod ami;
+mod batch;
+mod block_device;
+mod boot;
+mod cache;
+mod cancel;
+pub mod cloud;
+mod credentials;
+mod detect;
+mod diff;
+#[cfg(feature = "json")]
+mod disk_cache;
+mod dynamic;
+#[cfg(feature = "json")]
+pub mod ecs;
+pub mod env;
+mod events;
+#[cfg(feature = "json")]
+pub mod fixture;
+mod flat_json;
+mod from_imds;
+mod global;
+mod ids;
+mod instance_action;
+mod instance_type;
+mod instrument;
+mod lifecycle;
+mod metrics;
+mod middleware;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod network;
+#[cfg(feature = "opentelemetry")]
+mod otel;
+mod output;
+mod ping;
+mod placement;
+mod policy;
+#[cfg(feature = "prometheus")]
+mod prometheus;
+mod region;
+mod retry;
+#[cfg(feature = "aws-types")]
+mod sdk_region;
+mod shutdown;
+mod singleflight;
+#[cfg(feature = "json")]
+mod source;
+mod spot;
+mod stream;
+mod timestamp;
+mod token;
+pub mod transport;
+mod tree;
+mod verify;
+mod watcher;
+mod zone;
+
+pub use account::AccountIdSource;
+pub use ami::AmiInfo;
+pub use block_device::{BlockDeviceMapping, BlockDeviceRole};
+use cache::Cache;
+pub use cancel::CancellationToken;
+pub use credentials::{Credentials, IdentityCredentials};
+pub use detect::{is_ec2, is_ec2_with_timeout};
+pub use diff::{FieldChange, MetadataDiff};
+#[cfg(feature = "json")]
+use disk_cache::DiskCache;
+pub use dynamic::DynamicData;
+#[cfg(feature = "derive")]
+pub use ec2_instance_metadata_derive::FromImds;
+pub use env::EndpointMode;
+use env::EnvConfig;
+pub use events::{EventCode, EventState, InstanceEvent, InstanceEvents};
+pub use from_imds::{FromImds, ImdsValue};
+pub use global::{global, try_global};
+pub use ids::{AccountId, AmiId, InstanceId};
+pub use instance_action::InstanceAction;
+pub use instance_type::InstanceType;
+pub use lifecycle::{InstanceLifeCycle, TargetLifecycleState};
+use metrics::Observer;
+pub use metrics::{Outcome, RequestObserver};
+use middleware::Middlewares;
+pub use middleware::{Middleware, ResponseParts};
+pub use network::NetworkInterface;
+pub use ping::Availability;
+pub use placement::Placement;
+pub use policy::{FieldPolicy, MetadataField};
+pub use region::{Partition, Region};
+use retry::Retry;
+pub use retry::{ExponentialBackoff, RetryPolicy};
+pub use shutdown::{ShutdownReason, ShutdownSignal};
+use singleflight::SingleFlight;
+#[cfg(feature = "json")]
+pub use source::{DetectedMetadata, MetadataSource, SourceKind};
+pub use spot::{SpotInfo, SpotInstanceAction};
+pub use stream::MetadataReader;
+use token::TokenCache;
+pub use transport::ProxyMode;
+use transport::{
+    HasStatus, ImdsTransport, Method, Request, Response, StreamingResponse, TransportConfig,
+};
+pub use verify::{ConsistencyReport, FieldMismatch};
+pub use watcher::{MetadataWatcher, WatchEvent, Watcher};
+pub use zone::{AvailabilityZone, ZoneKind};
 
 #[derive(Clone, Copy)]
 enum MetadataUrls {
     InstanceId,
     AmiId,
-    AccountId,
     AvailabilityZone,
     InstanceType,
     Hostname,
     LocalHostname,
     PublicHostname,
+    PublicIpv4,
+    Tags,
 }
 
 #[allow(clippy::from_over_into)]
 impl Into<&'static str> for MetadataUrls {
     fn into(self) -> &'static str {
         match self {
-            MetadataUrls::InstanceId => "http://169.254.169.254/latest/meta-data/instance-id",
-            MetadataUrls::AmiId => "http://169.254.169.254/latest/meta-data/ami-id",
-            MetadataUrls::AccountId => {
-                "http://169.254.169.254/latest/meta-data/identity-credentials/ec2/info"
-            }
-            MetadataUrls::AvailabilityZone => {
-                "http://169.254.169.254/latest/meta-data/placement/availability-zone"
-            }
-            MetadataUrls::InstanceType => "http://169.254.169.254/latest/meta-data/instance-type",
-            MetadataUrls::Hostname => "http://169.254.169.254/latest/meta-data/hostname",
-            MetadataUrls::LocalHostname => "http://169.254.169.254/latest/meta-data/local-hostname",
-            MetadataUrls::PublicHostname => {
-                "http://169.254.169.254/latest/meta-data/public-hostname"
-            }
+            MetadataUrls::InstanceId => "meta-data/instance-id",
+            MetadataUrls::AmiId => "meta-data/ami-id",
+            MetadataUrls::AvailabilityZone => "meta-data/placement/availability-zone",
+            MetadataUrls::InstanceType => "meta-data/instance-type",
+            MetadataUrls::Hostname => "meta-data/hostname",
+            MetadataUrls::LocalHostname => "meta-data/local-hostname",
+            MetadataUrls::PublicHostname => "meta-data/public-hostname",
+            MetadataUrls::PublicIpv4 => "meta-data/public-ipv4",
+            MetadataUrls::Tags => "meta-data/tags/instance",
         }
     }
 }
 
-fn identity_credentials_to_account_id(ident_creds: &str) -> Result<String> {
-    let parsed: Value =
-        serde_json::from_str(ident_creds).map_err(|e| Error::JsonError(format!("{:?}", e)))?;
+// Only a 404 means the path doesn't exist; anything else unexpected is
+// reported with its status.
+pub(crate) fn check_status(status: u16, path: &str) -> Result<()> {
+    match status {
+        200..=299 => Ok(()),
+        404 => Err(Error::NotFound(path.to_string())),
+        status => Err(Error::HttpStatus(status, path.to_string())),
+    }
+}
 
-    parsed["AccountId"]
-        .as_str()
-        .map(|s| s.to_string())
-        .ok_or_else(|| Error::JsonError("Missing AccountId field".into()))
+// The error for an operation whose deadline passed before `path` was
+// fetched. The deadline may be the client's or one a caller set, such as
+// `get_with_deadline`'s, so it isn't named here.
+fn deadline_exceeded(path: &str) -> Error {
+    Error::DeadlineExceeded(format!("the deadline passed before {} was fetched", path))
 }
 
-fn availability_zone_to_region(availability_zone: &str) -> Result<&'static str> {
-    const REGIONS: &[&str] = &[
-        "ap-south-1",
-        "eu-west-3",
-        "eu-north-1",
-        "eu-west-2",
-        "eu-west-1",
-        "ap-northeast-3",
-        "ap-northeast-2",
-        "ap-northeast-1",
-        "sa-east-1",
-        "ca-central-1",
-        "ap-southeast-1",
-        "ap-southeast-2",
-        "eu-central-1",
-        "us-east-1",
-        "us-east-2",
-        "us-west-1",
-        "us-west-2",
-        "cn-north-1",
-        "cn-northwest-1",
-    ];
-
-    for region in REGIONS {
-        if availability_zone.starts_with(region) {
-            return Ok(region);
-        }
+// Treats a value IMDS doesn't serve (a 404) as `None`; any other failure
+// is still an error.
+pub(crate) fn missing_as_none(value: Result<String>) -> Result<Option<String>> {
+    match value {
+        Ok(value) => Ok(Some(value)),
+        Err(Error::NotFound(_)) => Ok(None),
+        Err(e) => Err(e),
     }
-
-    Err(Error::UnknownAvailabilityZone(
-        availability_zone.to_string(),
-    ))
 }
 
 type Result<T> = std::result::Result<T, Error>;
@@ -86,14 +177,20 @@ pub enum Error {
     HttpRequest(String),
     IoError(String),
     UnknownAvailabilityZone(String),
+    UnknownRegion(String),
+    UnknownPartition(String),
     JsonError(String),
-    NotFound(&'static str), // Reported for static URIs we fetch.
-}
-
-impl From<ureq::Error> for Error {
-    fn from(error: ureq::Error) -> Error {
-        Error::HttpRequest(format!("{:?}", error))
-    }
+    NotFound(String),         // Reported for metadata paths we fetch.
+    HopLimitExceeded(String), // The token PUT failed but IMDS answers GETs.
+    InvalidValue(String),     // A metadata value couldn't be parsed.
+    DeadlineExceeded(String), // An operation ran past its deadline.
+    Timeout(String),          // A single request timed out.
+    HttpStatus(u16, String),  // IMDS answered a path with an unexpected status.
+    Disabled,                 // IMDS access was turned off, e.g. by AWS_EC2_METADATA_DISABLED.
+    // A body was too large, or a text value had NULs or invalid UTF-8.
+    MalformedResponse(String),
+    Cancelled,      // The client's `CancellationToken` was cancelled.
+    NotEc2(String), // IMDS is unreachable and the machine isn't an EC2 instance.
 }
 
 impl From<std::io::Error> for Error {
@@ -102,6 +199,7 @@ impl From<std::io::Error> for Error {
     }
 }
 
+#[cfg(feature = "json")]
 impl From<serde_json::Error> for Error {
     fn from(error: serde_json::Error) -> Error {
         Error::JsonError(format!("{:?}", error))
@@ -114,8 +212,27 @@ impl std::fmt::Display for Error {
             Error::HttpRequest(s) => write!(f, "Http Request Error: {}", s),
             Error::IoError(s) => write!(f, "IO Error: {}", s),
             Error::UnknownAvailabilityZone(s) => write!(f, "Unknown AvailabilityZone: {}", s),
+            Error::UnknownRegion(s) => write!(f, "Unknown Region: {}", s),
+            Error::UnknownPartition(s) => write!(f, "Unknown Partition: {}", s),
             Error::JsonError(s) => write!(f, "JSON parsing error: {}", s),
             Error::NotFound(s) => write!(f, "Not found: {}", s),
+            Error::InvalidValue(s) => write!(f, "Invalid value: {}", s),
+            Error::DeadlineExceeded(s) => write!(f, "Deadline exceeded: {}", s),
+            Error::Timeout(s) => write!(f, "Request timed out: {}", s),
+            Error::Disabled => write!(f, "IMDS access is disabled"),
+            Error::MalformedResponse(s) => write!(f, "Malformed response: {}", s),
+            Error::Cancelled => write!(f, "Cancelled"),
+            Error::NotEc2(s) => write!(f, "Not an EC2 instance: {}", s),
+            Error::HttpStatus(status, path) => write!(f, "HTTP status {} for {}", status, path),
+            Error::HopLimitExceeded(s) => write!(
+                f,
+                "IMDSv2 token request failed ({}) but IMDS is reachable. The response was \
+                 likely dropped by the instance's metadata hop limit, which happens when \
+                 calling from a container. Raise the limit with `aws ec2 \
+                 modify-instance-metadata-options --instance-id <id> \
+                 --http-put-response-hop-limit 2`",
+                s
+            ),
         }
     }
 }
@@ -126,138 +243,753 @@ impl std::error::Error for Error {
     }
 }
 
+const LATEST_API_VERSION: &str = "latest";
 const REQUEST_TIMEOUT_MS: u64 = 2000; // 2 seconds
+const DEFAULT_MAX_ATTEMPTS: u32 = 3;
+const DEFAULT_MAX_CONCURRENCY: usize = 4;
+const IDLE_TIMEOUT_SECS: u64 = 15;
+// Far more than IMDS serves for any path; user data is capped at 16 KiB.
+pub(crate) const DEFAULT_MAX_BODY_SIZE: u64 = 1024 * 1024; // 1 MiB
+const DEFAULT_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"));
 
 /// `InstanceMetadataClient` provides an API for fetching common fields
 /// from the EC2 Instance Metadata API: https://docs.aws.amazon.com/AWSEC2/latest/UserGuide/ec2-instance-metadata.html
 ///
 /// # Examples:
-/// ```
+/// ```no_run
 /// use ec2_instance_metadata::InstanceMetadataClient;
 /// let client = ec2_instance_metadata::InstanceMetadataClient::new();
 /// let instance_metadata = client.get().expect("Couldn't get the instance metadata.");
-/// ````
+/// ```
+///
+/// Clones are cheap and share their token, cache and connections, so one
+/// client can be kept in shared state and cloned into each thread:
+/// ```
+/// # #[cfg(feature = "mock")] {
+/// use std::thread;
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// let server = MockImdsServer::start().unwrap();
+/// let client = server.client();
+/// client.get_path("meta-data/instance-id").unwrap();
+///
+/// let paths = ["ami-id", "hostname", "instance-type", "local-hostname"];
+/// let workers: Vec<_> = paths
+///     .iter()
+///     .map(|path| {
+///         let client = client.clone();
+///         let path = format!("meta-data/{}", path);
+///         thread::spawn(move || client.get_path(&path).unwrap())
+///     })
+///     .collect();
+/// for worker in workers {
+///     worker.join().unwrap();
+/// }
+/// // One token request, shared by the warm-up fetch and every worker.
+/// assert_eq!(server.request_count(), 6);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct InstanceMetadataClient {
+    inner: Arc<ClientInner>,
+}
 
+// Everything a client holds, shared between its clones.
 #[derive(Debug)]
-pub struct InstanceMetadataClient {
-    agent: ureq::Agent,
+struct ClientInner {
+    transport: Arc<dyn ImdsTransport>,
+    endpoint: String,
+    base_url: String,
+    deadline: Option<Duration>,
+    retry_policy: Retry,
+    disabled: bool,
+    cancel: CancellationToken,
+    max_body_size: u64,
+    max_concurrency: usize,
+    lenient: bool,
+    field_policy: FieldPolicy,
+    account_id_sources: Vec<AccountIdSource>,
+    observer: Option<Observer>,
+    middleware: Middlewares,
+    // Sent with every request, the `User-Agent` first.
+    headers: Vec<(String, String)>,
+    token: TokenCache,
+    cache: Option<Cache>,
+    #[cfg(feature = "json")]
+    disk_cache: Option<DiskCache>,
+    metadata_flight: SingleFlight<InstanceMetadata>,
+    path_flight: SingleFlight<String>,
 }
 impl InstanceMetadataClient {
     pub fn new() -> Self {
-        let agent = ureq::Agent::config_builder()
-            .timeout_connect(Some(std::time::Duration::from_millis(REQUEST_TIMEOUT_MS)))
-            .timeout_global(Some(std::time::Duration::from_millis(REQUEST_TIMEOUT_MS)))
-            .build()
-            .new_agent();
+        Self::builder().build()
+    }
 
-        Self { agent }
+    /// Returns a builder for configuring a client.
+    pub fn builder() -> InstanceMetadataClientBuilder {
+        InstanceMetadataClientBuilder::new()
     }
 
-    fn get_token(&self) -> Result<String> {
-        const TOKEN_API_URL: &str = "http://169.254.169.254/latest/api/token";
+    fn send(&self, request: &Request, path: &str) -> Result<Response> {
+        let resp = self.observed(request.clone(), path, 0, |request| {
+            self.inner.transport.send_streaming(request)
+        })?;
+        self.buffered(resp, path)
+    }
 
-        let mut resp = self
-            .agent
-            .put(TOKEN_API_URL)
-            .header("X-aws-ec2-metadata-token-ttl-seconds", "21600")
-            .send_empty()?;
+    // Reads a streamed response into memory, up to `max_body_size`. Every
+    // IMDS response is read this way, so a misbehaving proxy can't make
+    // the client buffer an unbounded body.
+    fn buffered(&self, resp: StreamingResponse, path: &str) -> Result<Response> {
+        Ok(Response {
+            status: resp.status,
+            headers: resp.headers,
+            body: stream::read_body(resp.body, self.inner.max_body_size, path)?,
+        })
+    }
 
-        let token = resp.body_mut().read_to_string()?;
-        Ok(token)
+    // Sends `request` once, through the middleware, the tracing span and
+    // the observer.
+    fn observed<R, F>(&self, mut request: Request, path: &str, retries: u32, send: F) -> Result<R>
+    where
+        R: HasStatus,
+        F: FnOnce(&Request) -> Result<R>,
+    {
+        let start = Instant::now();
+        request.headers.extend(self.inner.headers.iter().cloned());
+        let Middlewares(middleware) = &self.inner.middleware;
+        let result = middleware
+            .iter()
+            .try_for_each(|m| m.before_send(&mut request))
+            .and_then(|()| {
+                instrument::traced(&request, path, retries, || {
+                    let resp = send(&request)?;
+                    let parts = ResponseParts {
+                        path,
+                        status: resp.status(),
+                        headers: resp.headers(),
+                    };
+                    for m in middleware {
+                        m.after_response(&parts);
+                    }
+                    Ok(resp)
+                })
+            });
+        if let Some(Observer(observer)) = &self.inner.observer {
+            observer.on_request(path, start.elapsed(), Outcome::of(&result));
+        }
+        result
     }
 
-    /// Get the instance metadata for the machine.
-    pub fn get(&self) -> Result<InstanceMetadata> {
-        let token = self.get_token()?;
-        let instance_id = match self
-            .agent
-            .get::<&'static str>(MetadataUrls::InstanceId.into())
-            .header("X-aws-ec2-metadata-token", &token)
-            .call()
-        {
-            Ok(mut instance_id_resp) => instance_id_resp.body_mut().read_to_string()?,
-            Err(_) => return Err(Error::NotFound(MetadataUrls::InstanceId.into())),
-        };
+    // Sends `request`, retrying whatever the retry policy says to. The last
+    // response or error is returned as is once the policy gives up, or if
+    // waiting would pass `deadline`.
+    fn execute(
+        &self,
+        request: Request,
+        path: &str,
+        deadline: Option<Instant>,
+    ) -> Result<StreamingResponse> {
+        self.execute_with(request, path, deadline, |request| {
+            self.inner.transport.send_streaming(request)
+        })
+    }
+
+    fn execute_with<R, F>(
+        &self,
+        request: Request,
+        path: &str,
+        deadline: Option<Instant>,
+        send: F,
+    ) -> Result<R>
+    where
+        R: HasStatus,
+        F: Fn(&Request) -> Result<R>,
+    {
+        if self.inner.disabled {
+            return Err(Error::Disabled);
+        }
+
+        let mut retries = 0;
+        loop {
+            self.inner.cancel.check()?;
+            let request = self.within(request.clone(), path, deadline)?;
+            let result = self.observed(request, path, retries, &send);
+            if result.is_err() && deadline.is_some_and(|d| Instant::now() >= d) {
+                return Err(deadline_exceeded(path));
+            }
 
-        let account_id = match self
-            .agent
-            .get::<&'static str>(MetadataUrls::AccountId.into())
-            .header("X-aws-ec2-metadata-token", &token)
-            .call()
-        {
-            Ok(mut ident_creds_resp) => {
-                let ident_creds = ident_creds_resp.body_mut().read_to_string()?;
-                identity_credentials_to_account_id(&ident_creds)?
+            let Retry(policy) = &self.inner.retry_policy;
+            if !policy.should_retry(retries + 1, Outcome::of(&result)) {
+                return result;
+            }
+            let backoff = policy.delay(retries + 1);
+            if deadline.is_some_and(|d| Instant::now() + backoff >= d) {
+                return result;
             }
-            Err(_) => return Err(Error::NotFound(MetadataUrls::AccountId.into())),
+            self.inner.cancel.sleep(backoff)?;
+            retries += 1;
+            if let Some(Observer(observer)) = &self.inner.observer {
+                observer.on_retry(path, retries);
+            }
+        }
+    }
+
+    // Caps the request's timeout at whatever is left before `deadline`, or
+    // fails if there's nothing left.
+    fn within(&self, request: Request, path: &str, deadline: Option<Instant>) -> Result<Request> {
+        let deadline = match deadline {
+            Some(deadline) => deadline,
+            None => return Ok(request),
         };
 
-        let ami_id = match self
-            .agent
-            .get::<&'static str>(MetadataUrls::AmiId.into())
-            .header("X-aws-ec2-metadata-token", &token)
-            .call()
-        {
-            Ok(mut ami_id_resp) => ami_id_resp.body_mut().read_to_string()?,
-            Err(_) => return Err(Error::NotFound(MetadataUrls::AmiId.into())),
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        if remaining.is_zero() {
+            return Err(deadline_exceeded(path));
+        }
+        Ok(request.timeout(remaining))
+    }
+
+    pub(crate) fn get_token(&self) -> Result<String> {
+        self.token_within(None)
+    }
+
+    // Tokens are reused until shortly before they expire, or until IMDS
+    // rejects one.
+    fn token_within(&self, deadline: Option<Instant>) -> Result<String> {
+        if let Some(token) = self.inner.token.get() {
+            return Ok(token);
+        }
+
+        let token = self.request_token(deadline)?;
+        self.inner.token.put(token.clone());
+        Ok(token)
+    }
+
+    fn token_request(&self) -> Request {
+        // The token API only exists under `latest`, whatever version the
+        // metadata itself is read from.
+        let token_url = format!("{}/latest/api/token", self.inner.endpoint);
+        Request::new(Method::Put, token_url).header(
+            "X-aws-ec2-metadata-token-ttl-seconds",
+            &token::TOKEN_TTL_SECS.to_string(),
+        )
+    }
+
+    fn request_token(&self, deadline: Option<Instant>) -> Result<String> {
+        let request = self.token_request();
+        let resp = match self.execute(request, "api/token", deadline) {
+            Ok(resp) => resp,
+            Err(e @ Error::DeadlineExceeded(_))
+            | Err(e @ Error::Disabled)
+            | Err(e @ Error::Cancelled) => return Err(e),
+            // Simulators without the token API are read with IMDSv1.
+            Err(_) if self.inner.lenient => return Ok(String::new()),
+            Err(e) => return Err(self.diagnose_token_failure(e)),
         };
+        if !resp.is_success() {
+            if self.inner.lenient {
+                return Ok(String::new());
+            }
+            return Err(Error::HttpStatus(resp.status, "api/token".into()));
+        }
+
+        stream::read_value(resp.body, self.inner.max_body_size, "api/token")
+    }
+
+    // Whether IMDS hands out a token by `deadline`, without caching it or
+    // diagnosing a failure.
+    pub(crate) fn token_probe(&self, deadline: Option<Instant>) -> bool {
+        match self.execute(self.token_request(), "api/token", deadline) {
+            Ok(resp) => resp.is_success(),
+            Err(_) => false,
+        }
+    }
 
-        let (availability_zone, region) = match self
-            .agent
-            .get::<&'static str>(MetadataUrls::AvailabilityZone.into())
-            .header("X-aws-ec2-metadata-token", &token)
-            .call()
-        {
-            Ok(mut availability_zone_resp) => {
-                let zone = availability_zone_resp.body_mut().read_to_string()?;
-                let region = availability_zone_to_region(&zone)?;
-                (zone, region)
+    // The token PUT is the only IMDS response subject to the hop limit, so
+    // if it failed at the transport level but a plain GET gets any answer
+    // at all, the hop limit is the likely cause.
+    fn diagnose_token_failure(&self, error: Error) -> Error {
+        let probe = Request::new(Method::Get, format!("{}meta-data/", self.inner.base_url));
+        match self.send(&probe, "meta-data/") {
+            Ok(_) => Error::HopLimitExceeded(error.to_string()),
+            Err(_) => error,
+        }
+    }
+
+    fn fetch(&self, path: &str, token: &str) -> Result<String> {
+        self.fetch_within(path, token, None)
+    }
+
+    pub(crate) fn fetch_within(
+        &self,
+        path: &str,
+        token: &str,
+        deadline: Option<Instant>,
+    ) -> Result<String> {
+        let url = format!("{}{}", self.inner.base_url, path);
+        self.fetch_url(url, path, token, deadline)
+    }
+
+    fn fetch_url(
+        &self,
+        url: String,
+        path: &str,
+        token: &str,
+        deadline: Option<Instant>,
+    ) -> Result<String> {
+        let resp = self.get_with_token(&url, path, token, deadline, |request| {
+            self.inner.transport.send_streaming(request)
+        })?;
+        check_status(resp.status, path)?;
+        let text = stream::read_value(resp.body, self.inner.max_body_size, path)?;
+        if self.inner.lenient {
+            return Ok(text.trim().to_string());
+        }
+        Ok(text)
+    }
+
+    // GETs `url`, fetching a fresh token and trying again, once, if IMDS
+    // rejects `token` because it expired or was revoked since it was
+    // issued.
+    pub(crate) fn get_with_token<R, F>(
+        &self,
+        url: &str,
+        path: &str,
+        token: &str,
+        deadline: Option<Instant>,
+        send: F,
+    ) -> Result<R>
+    where
+        R: HasStatus,
+        F: Fn(&Request) -> Result<R>,
+    {
+        let get = |token: &str| {
+            let mut request = Request::new(Method::Get, url);
+            // An empty token means IMDSv1; see `simulator()`.
+            if !token.is_empty() {
+                request = request.header("X-aws-ec2-metadata-token", token);
             }
-            Err(_) => return Err(Error::NotFound(MetadataUrls::AvailabilityZone.into())),
+            self.execute_with(request, path, deadline, &send)
         };
 
-        let instance_type = match self
-            .agent
-            .get::<&'static str>(MetadataUrls::InstanceType.into())
-            .header("X-aws-ec2-metadata-token", &token)
-            .call()
-        {
-            Ok(mut instance_type_resp) => instance_type_resp.body_mut().read_to_string()?,
-            Err(_) => return Err(Error::NotFound(MetadataUrls::InstanceType.into())),
-        };
+        let resp = get(token)?;
+        if resp.status() != 401 {
+            return Ok(resp);
+        }
+        self.inner.token.invalidate(token);
+        get(&self.token_within(deadline)?)
+    }
 
-        let hostname = match self
-            .agent
-            .get::<&'static str>(MetadataUrls::Hostname.into())
-            .header("X-aws-ec2-metadata-token", &token)
-            .call()
-        {
-            Ok(mut hostname_resp) => hostname_resp.body_mut().read_to_string()?,
-            Err(_) => return Err(Error::NotFound(MetadataUrls::Hostname.into())),
-        };
+    /// Get the value stored at an arbitrary metadata path, relative to
+    /// `/latest/` (e.g. `meta-data/local-ipv4`).
+    pub fn get_path(&self, path: &str) -> Result<String> {
+        if let Some(value) = self.inner.cache.as_ref().and_then(|c| c.get_path(path)) {
+            return Ok(value);
+        }
+
+        // Concurrent callers asking for the same path share one request.
+        self.inner.path_flight.run(path, || {
+            let value = self.fetch_path(path)?;
+
+            if let Some(cache) = &self.inner.cache {
+                cache.put_path(path, value.clone());
+            }
+
+            Ok(value)
+        })
+    }
+
+    /// Get the whole response for an arbitrary metadata path, relative to
+    /// `/latest/`, for diagnosing IMDS proxies and simulators. Statuses
+    /// other than 2xx aren't treated as errors, and nothing is cached.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let client = server.client();
+    ///
+    /// let resp = client.get_raw("meta-data/instance-type").unwrap();
+    /// assert_eq!(resp.status, 200);
+    /// assert_eq!(resp.header("content-type"), Some("text/plain"));
+    /// assert_eq!(resp.body, b"t3.micro");
+    ///
+    /// assert_eq!(client.get_raw("meta-data/no-such-path").unwrap().status, 404);
+    /// # }
+    /// ```
+    ///
+    /// Bodies are still limited to `max_body_size`:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::Error;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set("user-data", &"x".repeat(64));
+    ///
+    /// let client = server.client_builder().max_body_size(16).build();
+    /// let result = client.get_raw("user-data");
+    /// assert!(matches!(result, Err(Error::MalformedResponse(_))));
+    /// # }
+    /// ```
+    pub fn get_raw(&self, path: &str) -> Result<Response> {
+        let token = self.get_token()?;
+        let url = format!("{}{}", self.inner.base_url, path);
+        let resp = self.get_with_token(&url, path, &token, None, |request| {
+            self.inner.transport.send_streaming(request)
+        })?;
+        self.buffered(resp, path)
+    }
+
+    // Like `get_path()`, but always goes to IMDS.
+    pub(crate) fn fetch_path(&self, path: &str) -> Result<String> {
+        let token = self.get_token()?;
+        self.fetch(path, &token)
+    }
+
+    /// List the API versions IMDS supports, oldest first. Any of them can
+    /// be pinned with `InstanceMetadataClientBuilder::api_version`.
+    pub fn list_api_versions(&self) -> Result<Vec<String>> {
+        let token = self.get_token()?;
+        let url = format!("{}/", self.inner.endpoint);
+        let versions = self.fetch_url(url, "/", &token, None)?;
+
+        Ok(versions
+            .lines()
+            .map(str::trim)
+            .filter(|v| !v.is_empty())
+            .map(str::to_string)
+            .collect())
+    }
+
+    /// Get the instance tags, if access to tags in instance metadata has
+    /// been enabled for the instance.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set("meta-data/tags/instance/Name", "web-1");
+    /// server.set("meta-data/tags/instance/team", "payments");
+    ///
+    /// let tags = server.client().get_tags().unwrap();
+    /// assert_eq!(tags["Name"], "web-1");
+    /// assert_eq!(tags.len(), 2);
+    /// # }
+    /// ```
+    pub fn get_tags(&self) -> Result<BTreeMap<String, String>> {
+        let tags_path: &str = MetadataUrls::Tags.into();
+        let token = self.get_token()?;
+        let keys = self.fetch(tags_path, &token)?;
+
+        let mut tags = BTreeMap::new();
+        for key in keys.lines().filter(|k| !k.is_empty()) {
+            let path = format!("{}/{}", tags_path, key);
+            tags.insert(key.to_string(), self.fetch(&path, &token)?);
+        }
+
+        Ok(tags)
+    }
 
-        let local_hostname = match self
-            .agent
-            .get::<&'static str>(MetadataUrls::LocalHostname.into())
-            .header("X-aws-ec2-metadata-token", &token)
-            .call()
-        {
-            Ok(mut local_hostname_resp) => local_hostname_resp.body_mut().read_to_string()?,
-            Err(_) => return Err(Error::NotFound(MetadataUrls::LocalHostname.into())),
+    /// Get the instance metadata for the machine. The session token is
+    /// reused between calls until shortly before it expires.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let client = server.client();
+    /// client.get().unwrap();
+    /// client.get().unwrap();
+    /// assert_eq!(server.token_count(), 1);
+    /// # }
+    /// ```
+    ///
+    /// Concurrent calls share one set of requests:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use std::sync::Barrier;
+    /// use std::thread;
+    /// use std::time::Duration;
+    /// use ec2_instance_metadata::mock::{MockImdsServer, MockTransport};
+    /// use ec2_instance_metadata::transport::{ImdsTransport, Request, Response};
+    /// use ec2_instance_metadata::{Error, InstanceMetadataClient};
+    ///
+    /// // Slow enough that every call starts before the first finishes.
+    /// #[derive(Debug)]
+    /// struct Slow(MockTransport);
+    /// impl ImdsTransport for Slow {
+    ///     fn send(&self, request: &Request) -> Result<Response, Error> {
+    ///         thread::sleep(Duration::from_millis(50));
+    ///         self.0.send(request)
+    ///     }
+    /// }
+    ///
+    /// let alone = MockImdsServer::start().unwrap();
+    /// alone.client().get().unwrap();
+    ///
+    /// let server = MockImdsServer::start().unwrap();
+    /// let client = InstanceMetadataClient::builder()
+    ///     .transport(Slow(server.transport()))
+    ///     .build();
+    /// let barrier = Barrier::new(8);
+    /// thread::scope(|scope| {
+    ///     for _ in 0..8 {
+    ///         scope.spawn(|| {
+    ///             barrier.wait();
+    ///             client.get().unwrap();
+    ///         });
+    ///     }
+    /// });
+    /// assert_eq!(server.request_count(), alone.request_count());
+    /// # }
+    /// ```
+    pub fn get(&self) -> Result<InstanceMetadata> {
+        self.get_within(self.operation_deadline())
+    }
+
+    // When an operation started now must finish by, if the client has a
+    // deadline.
+    // Whether the client talks to IMDS itself rather than to a simulator.
+    pub(crate) fn on_imds(&self) -> bool {
+        [EndpointMode::IPv4, EndpointMode::IPv6]
+            .iter()
+            .any(|mode| mode.endpoint() == self.inner.endpoint)
+    }
+
+    pub(crate) fn operation_deadline(&self) -> Option<Instant> {
+        self.inner.deadline.map(|d| Instant::now() + d)
+    }
+
+    pub(crate) fn get_within(&self, deadline: Option<Instant>) -> Result<InstanceMetadata> {
+        if let Some(metadata) = self.inner.cache.as_ref().and_then(|c| c.get_metadata()) {
+            return Ok(metadata);
+        }
+
+        // Concurrent callers share one set of requests.
+        self.inner.metadata_flight.run("", || {
+            #[cfg(feature = "json")]
+            let snapshot = self.load_disk_snapshot();
+            #[cfg(not(feature = "json"))]
+            let snapshot = None;
+
+            let metadata = match snapshot {
+                Some(metadata) => metadata,
+                None => {
+                    let metadata = self.fetch_metadata_within(deadline)?;
+                    #[cfg(feature = "json")]
+                    if let Some(disk_cache) = &self.inner.disk_cache {
+                        disk_cache.store(&metadata);
+                    }
+                    metadata
+                }
+            };
+
+            if let Some(cache) = &self.inner.cache {
+                cache.put_metadata(metadata.clone());
+            }
+
+            Ok(metadata)
+        })
+    }
+
+    // A fresh snapshot from the disk cache, as long as it was taken on
+    // this instance. An AMI baked from a running instance can carry the
+    // cache file along to instances launched from it.
+    #[cfg(feature = "json")]
+    fn load_disk_snapshot(&self) -> Option<InstanceMetadata> {
+        let mut metadata = self.inner.disk_cache.as_ref()?.load()?;
+        // The zone is stored by name, which doesn't say it's an Outpost.
+        if metadata.placement.is_on_outpost() {
+            metadata.availability_zone = metadata.availability_zone.on_outpost();
+        }
+
+        // The local instance id only says which instance IMDS describes
+        // when the client talks to the real IMDS, not to a simulator.
+        let instance_id = match self.on_imds().then(detect::dmi_instance_id).flatten() {
+            Some(instance_id) => instance_id,
+            None => self.fetch_path(MetadataUrls::InstanceId.into()).ok()?,
         };
+        if metadata.instance_id == instance_id.as_str() {
+            Some(metadata)
+        } else {
+            None
+        }
+    }
+
+    // Parses an id, except in lenient mode, where simulators can serve
+    // anything and invalid ids are kept as they are.
+    fn validated<T>(&self, value: String, unchecked: fn(String) -> T) -> Result<T>
+    where
+        T: std::str::FromStr<Err = Error>,
+    {
+        match value.parse() {
+            Err(_) if self.inner.lenient => Ok(unchecked(value)),
+            result => result,
+        }
+    }
+
+    pub(crate) fn fetch_metadata(&self) -> Result<InstanceMetadata> {
+        self.fetch_metadata_within(self.operation_deadline())
+    }
+
+    fn fetch_metadata_within(&self, deadline: Option<Instant>) -> Result<InstanceMetadata> {
+        let token = self.token_within(deadline)?;
+
+        // Only the first account id source is fetched up front; the rest
+        // are fallbacks, fetched only if it fails.
+        let account_id_path = self
+            .inner
+            .account_id_sources
+            .first()
+            .map(AccountIdSource::path);
+        let mut paths: Vec<&str> = vec![
+            MetadataUrls::InstanceId.into(),
+            MetadataUrls::AmiId.into(),
+            MetadataUrls::AvailabilityZone.into(),
+            MetadataUrls::InstanceType.into(),
+            MetadataUrls::Hostname.into(),
+            MetadataUrls::LocalHostname.into(),
+            MetadataUrls::PublicHostname.into(),
+            MetadataUrls::PublicIpv4.into(),
+            MetadataUrls::Tags.into(),
+            lifecycle::INSTANCE_LIFE_CYCLE,
+            network::SECURITY_GROUPS,
+            network::MAC,
+            region::SERVICES_PARTITION,
+        ];
+        paths.extend(placement::PLACEMENT_PATHS);
+        paths.extend(account_id_path.as_deref());
+        let values = self.fetch_many(&paths, &token, deadline);
+
+        let fetch = |path: &str| values[path].clone();
+        let optional = |path| missing_as_none(fetch(path));
+        let policy = &self.inner.field_policy;
+
+        let instance_id = self.validated(
+            fetch(MetadataUrls::InstanceId.into())?,
+            InstanceId::unchecked,
+        )?;
+
+        let account_id = self.fetch_account_id(|path| values.get(path).cloned(), &token, deadline);
+        let account_id = policy.apply(MetadataField::AccountId, account_id)?;
+
+        let ami_id = fetch(MetadataUrls::AmiId.into()).and_then(|ami_id| ami_id.parse());
+        let ami_id = policy.apply(MetadataField::AmiId, ami_id)?;
+
+        let availability_zone = fetch(MetadataUrls::AvailabilityZone.into())?;
+
+        let instance_type = fetch(MetadataUrls::InstanceType.into())?.parse()?;
+        let hostname = policy.apply(
+            MetadataField::Hostname,
+            fetch(MetadataUrls::Hostname.into()),
+        )?;
+        let local_hostname = policy.apply(
+            MetadataField::LocalHostname,
+            fetch(MetadataUrls::LocalHostname.into()),
+        )?;
 
         // "public-hostname" isn't always available - the instance must be configured
         // to support having one assigned.
-        let public_hostname = match self
-            .agent
-            .get::<&'static str>(MetadataUrls::PublicHostname.into())
-            .header("X-aws-ec2-metadata-token", &token)
-            .call()
-        {
-            Ok(mut public_hostname_resp) => Some(public_hostname_resp.body_mut().read_to_string()?),
-            Err(_) => None,
+        let public_hostname = policy.apply(
+            MetadataField::PublicHostname,
+            fetch(MetadataUrls::PublicHostname.into()),
+        )?;
+        let public_ipv4 = policy
+            .apply(
+                MetadataField::PublicIpv4,
+                fetch(MetadataUrls::PublicIpv4.into()),
+            )?
+            .map(|ip| ip.trim().to_string());
+
+        let placement = Placement::from_values(optional, self.inner.lenient)?;
+
+        // The region and partition come from IMDS, so regions launched
+        // after this release work too. Only when IMDS doesn't say are they
+        // worked out from the zone name and the regions this crate lists.
+        let partition = optional(region::SERVICES_PARTITION)?.and_then(|p| p.parse().ok());
+        let availability_zone = match &placement.region {
+            Some(region) => AvailabilityZone::in_region(
+                &availability_zone,
+                Region::reported(region, partition),
+            )?,
+            None => {
+                let zone: AvailabilityZone = availability_zone.parse()?;
+                let region = Region::reported(zone.region().name(), partition);
+                AvailabilityZone::in_region(zone.as_str(), region)?
+            }
+        };
+        let region = availability_zone.region();
+        let availability_zone = if placement.is_on_outpost() {
+            availability_zone.on_outpost()
+        } else {
+            availability_zone
         };
 
+        let instance_life_cycle = policy
+            .apply(
+                MetadataField::InstanceLifeCycle,
+                fetch(lifecycle::INSTANCE_LIFE_CYCLE),
+            )?
+            .map(|s| InstanceLifeCycle::from(s.as_str()));
+
+        let security_groups = policy
+            .apply(
+                MetadataField::SecurityGroups,
+                fetch(network::SECURITY_GROUPS),
+            )?
+            .map_or_else(Vec::new, |groups| network::lines(&groups));
+
+        let primary_mac = fetch(network::MAC).map(|mac| mac.trim().to_string());
+
+        // Tags are only listed if access to tags in instance metadata is
+        // enabled for the instance; otherwise the listing is a 404.
+        let tags_path: &str = MetadataUrls::Tags.into();
+        let tag_keys = policy
+            .apply(MetadataField::Tags, fetch(tags_path))?
+            .map_or_else(Vec::new, |keys| network::lines(&keys));
+        let tag_path = |key: &str| format!("{}/{}", tags_path, key);
+
+        // Security group ids, the VPC and the subnet are only listed per
+        // interface, so they're read from the primary one once its MAC
+        // address is known. Tag values are fetched alongside them.
+        let mut second_paths: Vec<String> = match &primary_mac {
+            Ok(mac) => network::PRIMARY_INTERFACE_FIELDS
+                .iter()
+                .map(|name| network::interface_path(mac, name))
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        second_paths.extend(tag_keys.iter().map(|key| tag_path(key)));
+        let second_paths: Vec<&str> = second_paths.iter().map(String::as_str).collect();
+        let second_values = self.fetch_many(&second_paths, &token, deadline);
+        let interface = |name: &str| -> Result<String> {
+            let path = network::interface_path(primary_mac.as_ref().map_err(Clone::clone)?, name);
+            second_values[&path].clone()
+        };
+
+        let tags = tag_keys
+            .iter()
+            .map(|key| Ok((key.clone(), second_values[&tag_path(key)].clone()?)))
+            .collect::<Result<BTreeMap<String, String>>>();
+        let tags = policy.apply(MetadataField::Tags, tags)?.unwrap_or_default();
+
+        let security_group_ids = policy
+            .apply(
+                MetadataField::SecurityGroupIds,
+                interface("security-group-ids"),
+            )?
+            .map_or_else(Vec::new, |ids| network::lines(&ids));
+        let vpc_id = policy
+            .apply(MetadataField::VpcId, interface("vpc-id"))?
+            .map(|id| id.trim().to_string());
+        let subnet_id = policy
+            .apply(MetadataField::SubnetId, interface("subnet-id"))?
+            .map(|id| id.trim().to_string());
+        let primary_mac = policy.apply(MetadataField::PrimaryMac, primary_mac)?;
+
         let metadata = InstanceMetadata {
             region,
             availability_zone,
@@ -268,50 +1000,659 @@ impl InstanceMetadataClient {
             hostname,
             local_hostname,
             public_hostname,
+            public_ipv4,
+            placement,
+            instance_life_cycle,
+            security_groups,
+            security_group_ids,
+            primary_mac,
+            vpc_id,
+            subnet_id,
+            tags,
         };
 
         Ok(metadata)
     }
+
+    /// Drop any cached metadata, in memory and on disk, so the next call
+    /// goes back to IMDS. Does nothing if caching isn't enabled.
+    pub fn clear_cache(&self) {
+        if let Some(cache) = &self.inner.cache {
+            cache.clear();
+        }
+        #[cfg(feature = "json")]
+        if let Some(disk_cache) = &self.inner.disk_cache {
+            disk_cache.clear();
+        }
+    }
+}
+
+/// `InstanceMetadataClientBuilder` configures an `InstanceMetadataClient`.
+///
+/// # Examples:
+/// ```
+/// use std::time::Duration;
+/// use ec2_instance_metadata::InstanceMetadataClient;
+/// let client = InstanceMetadataClient::builder()
+///     .cache_ttl(Duration::from_secs(300))
+///     .build();
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct InstanceMetadataClientBuilder {
+    transport: Option<Arc<dyn ImdsTransport>>,
+    endpoint: Option<String>,
+    endpoint_mode: Option<EndpointMode>,
+    connect_timeout: Option<Duration>,
+    read_timeout: Option<Duration>,
+    max_idle_connections: Option<usize>,
+    idle_timeout: Option<Duration>,
+    keep_alive: Option<bool>,
+    deadline: Option<Duration>,
+    max_attempts: Option<u32>,
+    retry_policy: Option<Retry>,
+    disabled: Option<bool>,
+    cancel: Option<CancellationToken>,
+    max_body_size: Option<u64>,
+    max_concurrency: Option<usize>,
+    lenient: bool,
+    field_policy: Option<FieldPolicy>,
+    account_id_sources: Option<Vec<AccountIdSource>>,
+    observer: Option<Observer>,
+    middleware: Middlewares,
+    user_agent: Option<String>,
+    headers: Vec<(String, String)>,
+    proxy: ProxyMode,
+    api_version: Option<String>,
+    cache_ttl: Option<Duration>,
+    #[cfg(feature = "json")]
+    disk_cache: Option<(PathBuf, Duration)>,
+}
+
+impl InstanceMetadataClientBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Send requests through `transport` instead of the default HTTP
+    /// backend. The timeout and proxy settings don't apply to custom
+    /// transports, but the `deadline` does.
+    pub fn transport(mut self, transport: impl ImdsTransport + 'static) -> Self {
+        self.transport = Some(Arc::new(transport));
+        self
+    }
+
+    /// Use a different IMDS endpoint, e.g. `http://127.0.0.1:1338` for a
+    /// local simulator. Defaults to `AWS_EC2_METADATA_SERVICE_ENDPOINT` if
+    /// it's set, or else the address for the `endpoint_mode`.
+    pub fn endpoint(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self
+    }
+
+    /// Talk to an IMDS simulator, such as amazon-ec2-metadata-mock or
+    /// LocalStack, at `endpoint`. Simulators vary in how closely they
+    /// follow IMDS, so the client is lenient with them: if the token
+    /// request fails it falls back to IMDSv1, values are trimmed, a
+    /// partition number which isn't a number is left out, and the account
+    /// id, AMI id and hostnames are left out unless a `field_policy`
+    /// requires them.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::InstanceMetadataClient;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set_status("api/token", 404);
+    /// server.allow_imdsv1(true);
+    /// server.set("meta-data/instance-type", "m4.xlarge\n");
+    /// server.remove("dynamic/instance-identity/document");
+    /// server.remove("meta-data/identity-credentials/ec2/info");
+    ///
+    /// let client = InstanceMetadataClient::builder()
+    ///     .simulator(server.endpoint())
+    ///     .build();
+    /// let metadata = client.get().unwrap();
+    /// assert_eq!(metadata.instance_type, "m4.xlarge");
+    /// assert_eq!(metadata.account_id, None);
+    /// # }
+    /// ```
+    pub fn simulator(mut self, endpoint: impl Into<String>) -> Self {
+        self.endpoint = Some(endpoint.into());
+        self.lenient = true;
+        self
+    }
+
+    /// Talk to the IPv4 or IPv6 IMDS address when no `endpoint` is set.
+    /// Defaults to `AWS_EC2_METADATA_SERVICE_ENDPOINT_MODE` if it's set,
+    /// or else IPv4.
+    pub fn endpoint_mode(mut self, mode: EndpointMode) -> Self {
+        self.endpoint_mode = Some(mode);
+        self
+    }
+
+    /// Read metadata from a specific API version, e.g. `2021-07-15`,
+    /// instead of `latest`, for a schema that doesn't change under you.
+    /// `InstanceMetadataClient::list_api_versions` lists the choices.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let client = server.client_builder().api_version("2021-07-15").build();
+    /// assert!(client.list_api_versions().unwrap().contains(&"2021-07-15".to_string()));
+    /// assert_eq!(client.get().unwrap().instance_type, "t3.micro");
+    /// # }
+    /// ```
+    pub fn api_version(mut self, version: impl Into<String>) -> Self {
+        self.api_version = Some(version.into());
+        self
+    }
+
+    /// Set both the connect and read timeouts to `timeout`.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.connect_timeout(timeout).read_timeout(timeout)
+    }
+
+    /// Give up on connecting to IMDS after `timeout`. Defaults to
+    /// `AWS_METADATA_SERVICE_TIMEOUT` if it's set, or else 2 seconds.
+    pub fn connect_timeout(mut self, timeout: Duration) -> Self {
+        self.connect_timeout = Some(timeout);
+        self
+    }
+
+    /// Give up waiting for a response after `timeout`, once connected.
+    /// Defaults to `AWS_METADATA_SERVICE_TIMEOUT` if it's set, or else 2
+    /// seconds.
+    pub fn read_timeout(mut self, timeout: Duration) -> Self {
+        self.read_timeout = Some(timeout);
+        self
+    }
+
+    /// Keep up to `connections` idle connections to IMDS open for reuse.
+    /// Defaults to 4, enough for `get()`'s concurrent fetches.
+    pub fn max_idle_connections(mut self, connections: usize) -> Self {
+        self.max_idle_connections = Some(connections);
+        self
+    }
+
+    /// Close idle connections after `timeout`. Defaults to 15 seconds.
+    pub fn idle_timeout(mut self, timeout: Duration) -> Self {
+        self.idle_timeout = Some(timeout);
+        self
+    }
+
+    /// Reuse connections between requests, which saves a TCP handshake on
+    /// every fetch for callers polling IMDS often. On by default; turn it
+    /// off to open a new connection for every request.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let client = server.client();
+    /// for _ in 0..5 {
+    ///     client.get_path("meta-data/instance-id").unwrap();
+    /// }
+    /// assert_eq!(server.connection_count(), 1);
+    ///
+    /// let server = MockImdsServer::start().unwrap();
+    /// let client = server.client_builder().keep_alive(false).build();
+    /// for _ in 0..5 {
+    ///     client.get_path("meta-data/instance-id").unwrap();
+    /// }
+    /// // Plus one for the token.
+    /// assert_eq!(server.connection_count(), 6);
+    /// # }
+    /// ```
+    pub fn keep_alive(mut self, keep_alive: bool) -> Self {
+        self.keep_alive = Some(keep_alive);
+        self
+    }
+
+    /// Fail `get()` with `Error::DeadlineExceeded` if all of its requests,
+    /// including the token request, haven't finished within `deadline`.
+    /// Each request is cut short to fit in whatever time is left. By
+    /// default there's no deadline beyond the per-request timeouts.
+    ///
+    /// # Examples:
+    /// ```
+    /// use std::time::Duration;
+    /// use ec2_instance_metadata::InstanceMetadataClient;
+    /// let client = InstanceMetadataClient::builder()
+    ///     .connect_timeout(Duration::from_millis(200))
+    ///     .read_timeout(Duration::from_secs(1))
+    ///     .deadline(Duration::from_secs(3))
+    ///     .build();
+    /// ```
+    pub fn deadline(mut self, deadline: Duration) -> Self {
+        self.deadline = Some(deadline);
+        self
+    }
+
+    /// Try each request up to `attempts` times in all when IMDS is
+    /// throttling or failing. Defaults to `AWS_METADATA_SERVICE_NUM_ATTEMPTS`
+    /// if it's set, or else 3. Ignored if there's a `retry_policy`.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts.max(1));
+        self
+    }
+
+    /// Decide which requests to retry, and how long to wait first, with
+    /// `policy` rather than `ExponentialBackoff`. See `RetryPolicy`.
+    pub fn retry_policy(mut self, policy: impl RetryPolicy + 'static) -> Self {
+        self.retry_policy = Some(Retry(Arc::new(policy)));
+        self
+    }
+
+    /// Fail every request with `Error::Disabled` without sending it,
+    /// as when `AWS_EC2_METADATA_DISABLED=true`. Passing `false`
+    /// re-enables a client the environment turned off.
+    pub fn disabled(mut self, disabled: bool) -> Self {
+        self.disabled = Some(disabled);
+        self
+    }
+
+    /// Fail requests with `Error::Cancelled` once `token` is cancelled.
+    /// See `CancellationToken`.
+    pub fn cancellation_token(mut self, token: CancellationToken) -> Self {
+        self.cancel = Some(token);
+        self
+    }
+
+    /// Report every request, its latency, and its outcome to `observer`,
+    /// e.g. to export IMDS health metrics. See `RequestObserver`.
+    pub fn observer(mut self, observer: impl RequestObserver + 'static) -> Self {
+        self.observer = Some(Observer(Arc::new(observer)));
+        self
+    }
+
+    /// Identify the client to IMDS as `user_agent`, e.g. `my-agent/1.2.0`,
+    /// so its requests can be told apart in IMDS access logs. Defaults to
+    /// `ec2_instance_metadata/<version>`, whatever the HTTP backend.
+    pub fn user_agent(mut self, user_agent: impl Into<String>) -> Self {
+        self.user_agent = Some(user_agent.into());
+        self
+    }
+
+    /// Send the header `name: value` with every request, including token
+    /// requests.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use std::sync::{Arc, Mutex};
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::transport::Request;
+    /// use ec2_instance_metadata::{Error, Middleware};
+    ///
+    /// struct Headers(Arc<Mutex<Vec<(String, String)>>>);
+    ///
+    /// impl Middleware for Headers {
+    ///     fn before_send(&self, request: &mut Request) -> Result<(), Error> {
+    ///         *self.0.lock().unwrap() = request.headers.clone();
+    ///         Ok(())
+    ///     }
+    /// }
+    ///
+    /// let sent = Arc::new(Mutex::new(Vec::new()));
+    /// let server = MockImdsServer::start().unwrap();
+    /// let client = server
+    ///     .client_builder()
+    ///     .user_agent("fleet-agent/2.1.0")
+    ///     .header("X-Fleet-Service", "billing")
+    ///     .middleware(Headers(Arc::clone(&sent)))
+    ///     .build();
+    /// client.get_path("meta-data/instance-id").unwrap();
+    ///
+    /// let sent = sent.lock().unwrap();
+    /// assert!(sent.contains(&("User-Agent".into(), "fleet-agent/2.1.0".into())));
+    /// assert!(sent.contains(&("X-Fleet-Service".into(), "billing".into())));
+    /// # }
+    /// ```
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// Run `middleware` before and after every request. See `Middleware`.
+    pub fn middleware(mut self, middleware: impl Middleware + 'static) -> Self {
+        self.middleware.0.push(Arc::new(middleware));
+        self
+    }
+
+    /// Fail any response larger than `bytes` with
+    /// `Error::MalformedResponse`, or for readers from
+    /// `InstanceMetadataClient::get_path_reader`, fail the read once it
+    /// passes `bytes`. Defaults to 1 MiB.
+    ///
+    /// Whatever the limit, values read as text, e.g. by `get_path()`, are
+    /// rejected the same way if they contain NUL bytes or invalid UTF-8.
+    /// Binary values such as gzipped `user-data` are legitimate, so read
+    /// them with `InstanceMetadataClient::get_path_reader`, which only
+    /// checks the size.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::Error;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set("meta-data/ami-id", "ami-0123456789abcdef0");
+    /// server.set("meta-data/instance-id", "i-0123\u{0}456789abcdef0");
+    ///
+    /// let client = server.client_builder().max_body_size(16).build();
+    /// let ami_id = client.get_path("meta-data/ami-id");
+    /// assert!(matches!(ami_id, Err(Error::MalformedResponse(_))));
+    ///
+    /// let instance_id = server.client().get_path("meta-data/instance-id");
+    /// assert!(matches!(instance_id, Err(Error::MalformedResponse(_))));
+    ///
+    /// // Binary user data is read as bytes, NULs and all.
+    /// use std::io::Read;
+    /// server.set("user-data", "\u{1f}\u{0}\u{8}\u{0}");
+    /// let mut user_data = Vec::new();
+    /// let reader = server.client().get_user_data_reader().unwrap();
+    /// reader.take(1024).read_to_end(&mut user_data).unwrap();
+    /// assert_eq!(user_data, [0x1f, 0x00, 0x08, 0x00]);
+    /// # }
+    /// ```
+    pub fn max_body_size(mut self, bytes: u64) -> Self {
+        self.max_body_size = Some(bytes);
+        self
+    }
+
+    /// Send at most `requests` requests at once when `get()` or
+    /// `get_many()` fetch several paths. Defaults to 4; 1 fetches one path
+    /// after another.
+    pub fn max_concurrency(mut self, requests: usize) -> Self {
+        self.max_concurrency = Some(requests);
+        self
+    }
+
+    /// Choose which fields `get()` fails without; see `FieldPolicy`.
+    pub fn field_policy(mut self, policy: FieldPolicy) -> Self {
+        self.field_policy = Some(policy);
+        self
+    }
+
+    /// Look for the account id in `sources`, in order, using the first
+    /// that works. Defaults to `AccountIdSource::default_chain()`: the
+    /// identity document, then the identity credentials, then the
+    /// `AccountId` instance tag.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::AccountIdSource;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set_status("dynamic/instance-identity/document", 403);
+    /// server.remove("meta-data/identity-credentials/ec2/info");
+    /// server.set("meta-data/tags/instance/AccountId", "210987654321");
+    /// server.set("meta-data/tags/instance/Owner", "999988887777");
+    ///
+    /// let account_id = server.client().get().unwrap().account_id.unwrap();
+    /// assert_eq!(account_id, "210987654321");
+    ///
+    /// let client = server
+    ///     .client_builder()
+    ///     .account_id_sources(vec![AccountIdSource::Tag("Owner".into())])
+    ///     .build();
+    /// assert_eq!(client.get().unwrap().account_id.unwrap(), "999988887777");
+    /// # }
+    /// ```
+    pub fn account_id_sources(mut self, sources: Vec<AccountIdSource>) -> Self {
+        self.account_id_sources = Some(sources);
+        self
+    }
+
+    /// Choose whether requests go through an HTTP proxy. By default proxy
+    /// environment variables are ignored for the link-local IMDS endpoint.
+    /// Doesn't apply to custom transports. A proxy URL the transport can't
+    /// parse fails every request with `Error::InvalidValue`.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(any(feature = "ureq", feature = "reqwest"))] {
+    /// use ec2_instance_metadata::{Error, InstanceMetadataClient, ProxyMode};
+    /// let client = InstanceMetadataClient::builder()
+    ///     .proxy(ProxyMode::Url("http://[::1".into()))
+    ///     .build();
+    /// let result = client.get_path("meta-data/instance-id");
+    /// assert!(matches!(result, Err(Error::InvalidValue(_))), "{:?}", result);
+    /// # }
+    /// ```
+    pub fn proxy(mut self, proxy: ProxyMode) -> Self {
+        self.proxy = proxy;
+        self
+    }
+
+    /// Cache fetched metadata in memory for `ttl`. Calls to `get()` and
+    /// `get_path()` within that window are served without hitting IMDS.
+    pub fn cache_ttl(mut self, ttl: Duration) -> Self {
+        self.cache_ttl = Some(ttl);
+        self
+    }
+
+    /// Persist the `get()` snapshot to the file at `path` for `ttl`, so
+    /// short-lived processes on the same host can reuse it. A cached
+    /// snapshot is only used if its instance id matches the instance's,
+    /// which is checked locally on Nitro instances talking to the default
+    /// IMDS endpoint and with a single IMDS request otherwise.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use std::time::Duration;
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let file = format!("ec2-metadata-doctest-{}.json", std::process::id());
+    /// let path = std::env::temp_dir().join(file);
+    /// let client = || server.client_builder().disk_cache(&path, Duration::from_secs(300)).build();
+    ///
+    /// client().get().unwrap();
+    /// server.set("meta-data/ami-id", "ami-0fedcba9876543210");
+    /// assert_eq!(client().get().unwrap().ami_id.unwrap(), "ami-0123456789abcdef0");
+    ///
+    /// // A different instance doesn't reuse the snapshot.
+    /// server.set("meta-data/instance-id", "i-0fedcba9876543210");
+    /// assert_eq!(client().get().unwrap().ami_id.unwrap(), "ami-0fedcba9876543210");
+    /// # std::fs::remove_file(&path).unwrap();
+    /// # }
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn disk_cache(mut self, path: impl Into<PathBuf>, ttl: Duration) -> Self {
+        self.disk_cache = Some((path.into(), ttl));
+        self
+    }
+
+    pub fn build(self) -> InstanceMetadataClient {
+        let lenient = self.lenient;
+        let env = EnvConfig::from_env();
+        let endpoint = match self.endpoint.or(env.endpoint) {
+            Some(endpoint) => endpoint,
+            None => {
+                let mode = self.endpoint_mode.or(env.endpoint_mode);
+                mode.unwrap_or_default().endpoint().to_string()
+            }
+        };
+        let endpoint = endpoint.as_str();
+        let default_timeout = env
+            .timeout
+            .unwrap_or(Duration::from_millis(REQUEST_TIMEOUT_MS));
+        let config = TransportConfig {
+            connect_timeout: self.connect_timeout.unwrap_or(default_timeout),
+            read_timeout: self.read_timeout.unwrap_or(default_timeout),
+            proxy: self.proxy.resolve(endpoint),
+            max_idle_connections: self.max_idle_connections.unwrap_or(DEFAULT_MAX_CONCURRENCY),
+            idle_timeout: self
+                .idle_timeout
+                .unwrap_or(Duration::from_secs(IDLE_TIMEOUT_SECS)),
+            keep_alive: self.keep_alive.unwrap_or(true),
+        };
+        let transport = self
+            .transport
+            .unwrap_or_else(|| transport::default_transport(&config).into());
+
+        let attempts = self.max_attempts.or(env.num_attempts);
+        let retry_policy = self.retry_policy.unwrap_or_else(|| {
+            let policy =
+                ExponentialBackoff::new().max_attempts(attempts.unwrap_or(DEFAULT_MAX_ATTEMPTS));
+            Retry(Arc::new(policy))
+        });
+
+        let user_agent = self.user_agent.as_deref().unwrap_or(DEFAULT_USER_AGENT);
+        let mut headers = vec![("User-Agent".to_string(), user_agent.to_string())];
+        headers.extend(self.headers);
+
+        let endpoint = endpoint.trim_end_matches('/').to_string();
+        let version = self.api_version.as_deref().unwrap_or(LATEST_API_VERSION);
+        let base_url = format!("{}/{}/", endpoint, version.trim_matches('/'));
+
+        let inner = ClientInner {
+            transport,
+            endpoint,
+            base_url,
+            deadline: self.deadline,
+            retry_policy,
+            disabled: self.disabled.or(env.disabled).unwrap_or(false),
+            cancel: self.cancel.unwrap_or_default(),
+            max_body_size: self.max_body_size.unwrap_or(DEFAULT_MAX_BODY_SIZE),
+            max_concurrency: self.max_concurrency.unwrap_or(DEFAULT_MAX_CONCURRENCY),
+            lenient,
+            // Simulators often leave out values every instance has.
+            field_policy: self.field_policy.unwrap_or_else(|| {
+                if lenient {
+                    FieldPolicy::none()
+                } else {
+                    FieldPolicy::default()
+                }
+            }),
+            account_id_sources: self
+                .account_id_sources
+                .unwrap_or_else(AccountIdSource::default_chain),
+            observer: self.observer,
+            middleware: self.middleware,
+            headers,
+            token: TokenCache::default(),
+            cache: self.cache_ttl.map(Cache::new),
+            #[cfg(feature = "json")]
+            disk_cache: self.disk_cache.map(|(path, ttl)| DiskCache::new(path, ttl)),
+            metadata_flight: SingleFlight::new(),
+            path_flight: SingleFlight::new(),
+        };
+        InstanceMetadataClient {
+            inner: Arc::new(inner),
+        }
+    }
 }
 
 /// `InstanceMetadata` holds the fetched instance metadata. Fields
 /// on this struct may be incomplete if AWS has updated the fields
 /// or if they haven't been explicitly provided.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
 pub struct InstanceMetadata {
-    /// AWS Region - always available
-    pub region: &'static str,
+    /// AWS Region and its partition - always available
+    pub region: Region,
 
-    /// AWS Availability Zone - always available
-    pub availability_zone: String,
+    /// AWS Availability Zone, Local Zone or Wavelength Zone - always
+    /// available
+    pub availability_zone: AvailabilityZone,
 
     /// AWS Instance Id - always available
-    pub instance_id: String,
+    pub instance_id: InstanceId,
 
-    /// AWS Account Id - always available, marked as Internal Only per:
+    /// AWS Account Id - required by default, marked as Internal Only per:
     /// https://docs.aws.amazon.com/AWSEC2/latest/UserGuide/instancedata-data-categories.html
-    pub account_id: String,
+    pub account_id: Option<AccountId>,
 
-    /// AWS AMS Id - always available
-    pub ami_id: String,
+    /// AWS AMS Id - required by default
+    pub ami_id: Option<AmiId>,
 
     /// AWS Instance Type - always available
-    pub instance_type: String,
+    pub instance_type: InstanceType,
 
-    /// AWS Instance Local Hostname - always available
-    pub local_hostname: String,
+    /// AWS Instance Local Hostname - required by default
+    pub local_hostname: Option<String>,
 
-    /// AWS Instance Hostname - always available
-    pub hostname: String,
+    /// AWS Instance Hostname - required by default
+    pub hostname: Option<String>,
 
     /// AWS Instance Public Hostname - optionally available
     pub public_hostname: Option<String>,
-}
 
-impl std::fmt::Display for InstanceMetadata {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f, "{:?}", self)
-    }
+    /// Public IPv4 address, e.g. an Elastic IP - optionally available
+    pub public_ipv4: Option<String>,
+
+    /// AWS Instance Placement - fields are individually optional
+    pub placement: Placement,
+
+    /// AWS Instance Purchasing Option (spot, on-demand, ...) - optionally available
+    pub instance_life_cycle: Option<InstanceLifeCycle>,
+
+    /// Names of the instance's security groups - empty if not available
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let metadata = server.client().get().unwrap();
+    /// assert_eq!(metadata.security_groups, ["default", "web"]);
+    /// assert_eq!(
+    ///     metadata.security_group_ids,
+    ///     ["sg-0123456789abcdef0", "sg-0fedcba9876543210"]
+    /// );
+    ///
+    /// server.remove("meta-data/security-groups");
+    /// assert!(server.client().get().unwrap().security_groups.is_empty());
+    /// # }
+    /// ```
+    pub security_groups: Vec<String>,
+
+    /// Ids of the primary network interface's security groups - empty if
+    /// not available
+    pub security_group_ids: Vec<String>,
+
+    /// MAC address of the primary network interface, which IMDS keys its
+    /// network details by - optionally available
+    pub primary_mac: Option<String>,
+
+    /// Id of the primary network interface's VPC (e.g.
+    /// `vpc-0123456789abcdef0`) - optionally available
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let metadata = server.client().get().unwrap();
+    /// assert_eq!(metadata.vpc_id.as_deref(), Some("vpc-0123456789abcdef0"));
+    /// assert_eq!(metadata.subnet_id.as_deref(), Some("subnet-0123456789abcdef0"));
+    ///
+    /// // Both come from whichever interface `meta-data/mac` names.
+    /// let interface = "meta-data/network/interfaces/macs/0e:49:61:0f:c3:22";
+    /// server.set("meta-data/mac", "0e:49:61:0f:c3:22");
+    /// server.set(&format!("{}/vpc-id", interface), "vpc-0fedcba9876543210");
+    /// let metadata = server.client().get().unwrap();
+    /// assert_eq!(metadata.vpc_id.as_deref(), Some("vpc-0fedcba9876543210"));
+    /// assert_eq!(metadata.subnet_id, None);
+    /// # }
+    /// ```
+    pub vpc_id: Option<String>,
+
+    /// Id of the primary network interface's subnet (e.g.
+    /// `subnet-0123456789abcdef0`) - optionally available
+    pub subnet_id: Option<String>,
+
+    /// The instance's tags - empty unless access to tags in instance
+    /// metadata is enabled for the instance
+    #[cfg_attr(feature = "json", serde(default))]
+    pub tags: BTreeMap<String, String>,
 }
 
 impl Default for InstanceMetadataClient {
@@ -319,3 +1660,10 @@ impl Default for InstanceMetadataClient {
         Self::new()
     }
 }
+
+// Clients are meant to be shared between threads; this stops compiling if
+// a field ever makes them not `Send` or `Sync`.
+const _: fn() = || {
+    fn assert_thread_safe<T: Clone + Send + Sync + 'static>() {}
+    assert_thread_safe::<InstanceMetadataClient>();
+};
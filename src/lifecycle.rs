@@ -0,0 +1,223 @@
+use std::time::Duration;
+
+#[cfg(feature = "json")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{Error, InstanceMetadataClient, Result, Watcher};
+
+pub(crate) const INSTANCE_LIFE_CYCLE: &str = "meta-data/instance-life-cycle";
+const AUTOSCALING_TARGET_LIFECYCLE_STATE: &str = "meta-data/autoscaling/target-lifecycle-state";
+
+/// `InstanceLifeCycle` is the purchasing option the instance was launched
+/// with.
+///
+/// # Examples:
+/// ```
+/// # #[cfg(feature = "mock")] {
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// use ec2_instance_metadata::InstanceLifeCycle;
+/// let server = MockImdsServer::start().unwrap();
+/// let life_cycle = || server.client().get().unwrap().instance_life_cycle;
+/// assert_eq!(life_cycle(), Some(InstanceLifeCycle::OnDemand));
+///
+/// server.set("meta-data/instance-life-cycle", "capacity-block");
+/// assert_eq!(life_cycle(), Some(InstanceLifeCycle::CapacityBlock));
+///
+/// server.set("meta-data/instance-life-cycle", "reserved-capacity");
+/// let other = life_cycle().unwrap();
+/// assert_eq!(other, InstanceLifeCycle::Other("reserved-capacity".into()));
+/// assert_eq!(other.to_string(), "reserved-capacity");
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum InstanceLifeCycle {
+    OnDemand,
+    Spot,
+    Scheduled,
+    CapacityBlock,
+    /// A value this crate doesn't know about yet
+    Other(String),
+}
+
+impl InstanceLifeCycle {
+    pub fn as_str(&self) -> &str {
+        match self {
+            InstanceLifeCycle::OnDemand => "on-demand",
+            InstanceLifeCycle::Spot => "spot",
+            InstanceLifeCycle::Scheduled => "scheduled",
+            InstanceLifeCycle::CapacityBlock => "capacity-block",
+            InstanceLifeCycle::Other(s) => s,
+        }
+    }
+}
+
+impl From<&str> for InstanceLifeCycle {
+    fn from(s: &str) -> Self {
+        match s.trim() {
+            "on-demand" => InstanceLifeCycle::OnDemand,
+            "spot" => InstanceLifeCycle::Spot,
+            "scheduled" => InstanceLifeCycle::Scheduled,
+            "capacity-block" => InstanceLifeCycle::CapacityBlock,
+            other => InstanceLifeCycle::Other(other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for InstanceLifeCycle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for InstanceLifeCycle {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'de> Deserialize<'de> for InstanceLifeCycle {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(InstanceLifeCycle::from(s.as_str()))
+    }
+}
+
+/// `TargetLifecycleState` is the state Amazon EC2 Auto Scaling is moving
+/// the instance to. Lifecycle hooks should watch for `Terminated` and the
+/// warm pool states to coordinate a graceful shutdown or hand-off.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum TargetLifecycleState {
+    InService,
+    Standby,
+    Detached,
+    Terminated,
+    WarmedStopped,
+    WarmedRunning,
+    WarmedHibernated,
+    WarmedTerminated,
+    /// A value this crate doesn't know about yet
+    Other(String),
+}
+
+impl TargetLifecycleState {
+    pub fn as_str(&self) -> &str {
+        match self {
+            TargetLifecycleState::InService => "InService",
+            TargetLifecycleState::Standby => "Standby",
+            TargetLifecycleState::Detached => "Detached",
+            TargetLifecycleState::Terminated => "Terminated",
+            TargetLifecycleState::WarmedStopped => "Warmed:Stopped",
+            TargetLifecycleState::WarmedRunning => "Warmed:Running",
+            TargetLifecycleState::WarmedHibernated => "Warmed:Hibernated",
+            TargetLifecycleState::WarmedTerminated => "Warmed:Terminated",
+            TargetLifecycleState::Other(s) => s,
+        }
+    }
+
+    /// True for any of the `Warmed:*` states.
+    pub fn is_warmed(&self) -> bool {
+        self.as_str().starts_with("Warmed:")
+    }
+
+    /// True if the instance is on its way out of service for good.
+    pub fn is_terminating(&self) -> bool {
+        matches!(
+            self,
+            TargetLifecycleState::Terminated | TargetLifecycleState::WarmedTerminated
+        )
+    }
+}
+
+impl From<&str> for TargetLifecycleState {
+    fn from(s: &str) -> Self {
+        match s.trim() {
+            "InService" => TargetLifecycleState::InService,
+            "Standby" => TargetLifecycleState::Standby,
+            "Detached" => TargetLifecycleState::Detached,
+            "Terminated" => TargetLifecycleState::Terminated,
+            "Warmed:Stopped" => TargetLifecycleState::WarmedStopped,
+            "Warmed:Running" => TargetLifecycleState::WarmedRunning,
+            "Warmed:Hibernated" => TargetLifecycleState::WarmedHibernated,
+            "Warmed:Terminated" => TargetLifecycleState::WarmedTerminated,
+            other => TargetLifecycleState::Other(other.to_string()),
+        }
+    }
+}
+
+impl std::fmt::Display for TargetLifecycleState {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for TargetLifecycleState {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl InstanceMetadataClient {
+    /// Get the Auto Scaling target lifecycle state, or `None` if the
+    /// instance isn't part of an Auto Scaling group.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::TargetLifecycleState;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let client = server.client();
+    /// assert_eq!(client.get_target_lifecycle_state().unwrap(), None);
+    ///
+    /// server.set("meta-data/autoscaling/target-lifecycle-state", "Warmed:Stopped");
+    /// let state = client.get_target_lifecycle_state().unwrap().unwrap();
+    /// assert_eq!(state, TargetLifecycleState::WarmedStopped);
+    /// assert!(state.is_warmed());
+    /// # }
+    /// ```
+    pub fn get_target_lifecycle_state(&self) -> Result<Option<TargetLifecycleState>> {
+        match self.fetch_path(AUTOSCALING_TARGET_LIFECYCLE_STATE) {
+            Ok(state) => Ok(Some(TargetLifecycleState::from(state.as_str()))),
+            Err(Error::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Start a `Watcher` which polls the Auto Scaling target lifecycle state
+    /// every `interval`.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use std::time::Duration;
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::{TargetLifecycleState, WatchEvent};
+    /// let server = MockImdsServer::start().unwrap();
+    /// let path = "meta-data/autoscaling/target-lifecycle-state";
+    /// server.set(path, "InService");
+    /// let watcher = server
+    ///     .client()
+    ///     .watch_target_lifecycle_state(Duration::from_millis(50))
+    ///     .unwrap();
+    /// assert_eq!(*watcher.current(), Some(TargetLifecycleState::InService));
+    ///
+    /// server.set(path, "Terminated");
+    /// match watcher.events().recv_timeout(Duration::from_secs(5)).unwrap() {
+    ///     WatchEvent::Changed { previous, current } => {
+    ///         assert_eq!(*previous, Some(TargetLifecycleState::InService));
+    ///         assert!(current.as_ref().as_ref().unwrap().is_terminating());
+    ///     }
+    ///     event => panic!("unexpected {:?}", event),
+    /// }
+    /// # }
+    /// ```
+    pub fn watch_target_lifecycle_state(
+        self,
+        interval: Duration,
+    ) -> Result<Watcher<Option<TargetLifecycleState>>> {
+        Watcher::spawn(interval, move || self.get_target_lifecycle_state())
+    }
+}
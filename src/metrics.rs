@@ -0,0 +1,90 @@
+//! Hooks for tracking the health of IMDS calls from the host's own
+//! metrics system, e.g. Prometheus or StatsD.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::transport::HasStatus;
+use crate::{Error, Result};
+
+/// How a single IMDS request ended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Outcome {
+    /// IMDS answered, with this status. Statuses other than 2xx still
+    /// count as answers.
+    Status(u16),
+    /// The request timed out before IMDS answered.
+    Timeout,
+    /// The request failed before IMDS answered, e.g. because the
+    /// connection was refused.
+    Error,
+}
+
+impl Outcome {
+    pub(crate) fn of<R: HasStatus>(result: &Result<R>) -> Self {
+        match result {
+            Ok(resp) => Outcome::Status(resp.status()),
+            Err(Error::Timeout(_)) => Outcome::Timeout,
+            Err(_) => Outcome::Error,
+        }
+    }
+
+    /// True if IMDS answered with a 2xx status.
+    pub fn is_success(self) -> bool {
+        matches!(self, Outcome::Status(status) if (200..300).contains(&status))
+    }
+}
+
+/// `RequestObserver` is told about every request the client sends,
+/// including token requests and retries.
+///
+/// Closures taking `(path, duration, outcome)` implement it too.
+///
+/// # Examples:
+/// ```
+/// use std::sync::atomic::{AtomicUsize, Ordering};
+/// use std::sync::Arc;
+/// use std::time::Duration;
+/// use ec2_instance_metadata::{InstanceMetadataClient, Outcome};
+///
+/// let failures = Arc::new(AtomicUsize::new(0));
+/// let counter = Arc::clone(&failures);
+/// let client = InstanceMetadataClient::builder()
+///     .observer(move |_path: &str, _duration: Duration, outcome: Outcome| {
+///         if !outcome.is_success() {
+///             counter.fetch_add(1, Ordering::Relaxed);
+///         }
+///     })
+///     .build();
+/// ```
+pub trait RequestObserver: Send + Sync {
+    /// Called after each request with the path it was for, relative to
+    /// the API version (e.g. `meta-data/instance-id` or `api/token`), and
+    /// how long it took.
+    fn on_request(&self, path: &str, duration: Duration, outcome: Outcome);
+
+    /// Called before a request is retried, with the number of retries
+    /// made so far, counting this one.
+    fn on_retry(&self, _path: &str, _retries: u32) {}
+}
+
+impl<F> RequestObserver for F
+where
+    F: Fn(&str, Duration, Outcome) + Send + Sync,
+{
+    fn on_request(&self, path: &str, duration: Duration, outcome: Outcome) {
+        self(path, duration, outcome)
+    }
+}
+
+// Observers are usually closures, which aren't `Debug`, but the builder
+// and client are.
+#[derive(Clone)]
+pub(crate) struct Observer(pub(crate) Arc<dyn RequestObserver>);
+
+impl fmt::Debug for Observer {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("Observer")
+    }
+}
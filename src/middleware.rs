@@ -0,0 +1,97 @@
+//! Hooks run around every request the client sends, for adding headers,
+//! audit logging, or injecting faults in tests.
+
+use std::fmt;
+use std::sync::Arc;
+
+use crate::transport::Request;
+use crate::Result;
+
+/// The parts of a response a `Middleware` gets to see. The body isn't
+/// included, since streamed responses haven't been read yet.
+#[derive(Debug, Clone, Copy)]
+pub struct ResponseParts<'a> {
+    /// The path the request was for, relative to the API version (e.g.
+    /// `meta-data/instance-id` or `api/token`)
+    pub path: &'a str,
+    pub status: u16,
+    pub headers: &'a [(String, String)],
+}
+
+impl ResponseParts<'_> {
+    /// Look up a header, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// `Middleware` runs before and after every request the client sends,
+/// including token requests and retries. Middleware added to the builder
+/// runs in the order it was added.
+///
+/// # Examples:
+/// ```
+/// # #[cfg(feature = "mock")] {
+/// use std::sync::{Arc, Mutex};
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// use ec2_instance_metadata::transport::Request;
+/// use ec2_instance_metadata::{Error, Middleware, ResponseParts};
+///
+/// // Fails requests for one path, to test how the caller copes.
+/// struct Chaos(&'static str);
+///
+/// impl Middleware for Chaos {
+///     fn before_send(&self, request: &mut Request) -> Result<(), Error> {
+///         if request.url.ends_with(self.0) {
+///             return Err(Error::HttpRequest("injected failure".into()));
+///         }
+///         Ok(())
+///     }
+/// }
+///
+/// struct Audit(Arc<Mutex<Vec<String>>>);
+///
+/// impl Middleware for Audit {
+///     fn after_response(&self, response: &ResponseParts) {
+///         let entry = format!("{} {}", response.status, response.path);
+///         self.0.lock().unwrap().push(entry);
+///     }
+/// }
+///
+/// let log = Arc::new(Mutex::new(Vec::new()));
+/// let server = MockImdsServer::start().unwrap();
+/// let client = server
+///     .client_builder()
+///     .middleware(Chaos("meta-data/ami-id"))
+///     .middleware(Audit(Arc::clone(&log)))
+///     .build();
+/// assert!(client.get_path("meta-data/ami-id").is_err());
+/// client.get_path("meta-data/instance-id").unwrap();
+/// assert_eq!(*log.lock().unwrap(), ["200 api/token", "200 meta-data/instance-id"]);
+/// # }
+/// ```
+pub trait Middleware: Send + Sync {
+    /// Called before each request is sent. It can change the request,
+    /// e.g. to add headers, or fail it without sending it.
+    fn before_send(&self, _request: &mut Request) -> Result<()> {
+        Ok(())
+    }
+
+    /// Called after each response arrives, before the client looks at it.
+    /// Requests which failed without a response aren't reported.
+    fn after_response(&self, _response: &ResponseParts) {}
+}
+
+// Middleware are usually user types, which needn't be `Debug`, but the
+// builder and client are.
+#[derive(Clone, Default)]
+pub(crate) struct Middlewares(pub(crate) Vec<Arc<dyn Middleware>>);
+
+impl fmt::Debug for Middlewares {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Middlewares({})", self.0.len())
+    }
+}
@@ -0,0 +1,553 @@
+//! An in-process IMDS simulator for testing code that depends on
+//! `InstanceMetadataClient` without running on EC2.
+//!
+//! The server speaks just enough HTTP/1.1 to serve the IMDSv2 token
+//! exchange and plain-text metadata values, including directory listings
+//! for paths that have children. The same simulated IMDS is also available
+//! as an `ImdsTransport`, which skips the network entirely.
+//!
+//! # Examples:
+//! ```
+//! use ec2_instance_metadata::mock::MockImdsServer;
+//! let server = MockImdsServer::start().unwrap();
+//! server.set("meta-data/instance-type", "c7g.2xlarge");
+//!
+//! let metadata = server.client().get().unwrap();
+//! assert_eq!(metadata.instance_type, "c7g.2xlarge");
+//! assert_eq!(metadata.region, "us-east-1");
+//! ```
+
+use std::collections::{BTreeMap, BTreeSet, HashSet};
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread;
+use std::time::Duration;
+
+use crate::transport::{ImdsTransport, Request};
+use crate::{transport, Error, InstanceMetadataClient, InstanceMetadataClientBuilder, Result};
+
+/// Values served by `MockImdsServer::start()`, keyed by path relative to
+/// `/latest/`.
+pub const DEFAULT_VALUES: &[(&str, &str)] = &[
+    ("meta-data/instance-id", "i-0123456789abcdef0"),
+    ("meta-data/ami-id", "ami-0123456789abcdef0"),
+    ("meta-data/ami-launch-index", "0"),
+    ("meta-data/block-device-mapping/ami", "/dev/xvda"),
+    ("meta-data/block-device-mapping/root", "/dev/xvda"),
+    ("meta-data/ami-manifest-path", "(unknown)"),
+    ("meta-data/reservation-id", "r-0123456789abcdef0"),
+    (
+        "meta-data/identity-credentials/ec2/info",
+        r#"{"Code":"Success","LastUpdated":"2024-01-01T00:00:00Z","AccountId":"123456789012"}"#,
+    ),
+    (
+        "meta-data/identity-credentials/ec2/security-credentials/ec2-instance",
+        r#"{"Code":"Success","LastUpdated":"2024-01-01T00:00:00Z","Type":"AWS-HMAC","AccessKeyId":"ASIAEXAMPLE","SecretAccessKey":"secret","Token":"token","Expiration":"2099-01-01T00:00:00Z"}"#,
+    ),
+    ("meta-data/placement/availability-zone", "us-east-1a"),
+    ("meta-data/placement/availability-zone-id", "use1-az4"),
+    ("meta-data/placement/region", "us-east-1"),
+    ("meta-data/instance-type", "t3.micro"),
+    ("meta-data/instance-life-cycle", "on-demand"),
+    ("meta-data/services/partition", "aws"),
+    ("meta-data/services/domain", "amazonaws.com"),
+    ("meta-data/hostname", "ip-10-0-0-1.ec2.internal"),
+    ("meta-data/local-hostname", "ip-10-0-0-1.ec2.internal"),
+    (
+        "meta-data/public-hostname",
+        "ec2-203-0-113-1.compute-1.amazonaws.com",
+    ),
+    ("meta-data/security-groups", "default\nweb"),
+    ("dynamic/fws/instance-monitoring", "disabled"),
+    (
+        "dynamic/instance-identity/document",
+        r#"{"accountId":"123456789012","architecture":"x86_64","availabilityZone":"us-east-1a","imageId":"ami-0123456789abcdef0","instanceId":"i-0123456789abcdef0","instanceType":"t3.micro","pendingTime":"2024-01-01T00:00:00Z","privateIp":"10.0.0.1","region":"us-east-1","version":"2017-09-30"}"#,
+    ),
+    ("meta-data/mac", "0e:49:61:0f:c3:11"),
+    (
+        "meta-data/network/interfaces/macs/0e:49:61:0f:c3:11/interface-id",
+        "eni-0123456789abcdef0",
+    ),
+    (
+        "meta-data/network/interfaces/macs/0e:49:61:0f:c3:11/device-number",
+        "0",
+    ),
+    (
+        "meta-data/network/interfaces/macs/0e:49:61:0f:c3:11/vpc-id",
+        "vpc-0123456789abcdef0",
+    ),
+    (
+        "meta-data/network/interfaces/macs/0e:49:61:0f:c3:11/subnet-id",
+        "subnet-0123456789abcdef0",
+    ),
+    (
+        "meta-data/network/interfaces/macs/0e:49:61:0f:c3:11/owner-id",
+        "123456789012",
+    ),
+    (
+        "meta-data/network/interfaces/macs/0e:49:61:0f:c3:11/local-ipv4s",
+        "10.0.0.1",
+    ),
+    (
+        "meta-data/network/interfaces/macs/0e:49:61:0f:c3:11/public-ipv4s",
+        "203.0.113.1",
+    ),
+    (
+        "meta-data/network/interfaces/macs/0e:49:61:0f:c3:11/ipv4-associations/203.0.113.1",
+        "10.0.0.1",
+    ),
+    (
+        "meta-data/network/interfaces/macs/0e:49:61:0f:c3:11/security-group-ids",
+        "sg-0123456789abcdef0\nsg-0fedcba9876543210",
+    ),
+];
+
+/// API versions the server answers to. Every version serves the same
+/// values.
+pub const API_VERSIONS: &[&str] = &["1.0", "2021-07-15", "latest"];
+
+#[derive(Debug, Default)]
+struct State {
+    values: BTreeMap<String, String>,
+    statuses: BTreeMap<String, u16>,
+    tokens: HashSet<String>,
+    imdsv1: bool,
+    drop_tokens: bool,
+    issued: usize,
+    requests: usize,
+    connections: usize,
+}
+
+fn lock(state: &Mutex<State>) -> MutexGuard<'_, State> {
+    state.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// `MockImdsServer` runs a simulated IMDS on a random local port until it
+/// is dropped.
+#[derive(Debug)]
+pub struct MockImdsServer {
+    addr: SocketAddr,
+    state: Arc<Mutex<State>>,
+    shutdown: Arc<AtomicBool>,
+}
+
+impl MockImdsServer {
+    /// Start a server populated with `DEFAULT_VALUES`.
+    pub fn start() -> io::Result<Self> {
+        let server = Self::start_empty()?;
+        for (path, value) in DEFAULT_VALUES {
+            server.set(path, value);
+        }
+        Ok(server)
+    }
+
+    /// Start a server with no metadata values; every GET returns 404 until
+    /// values are added with `set()`.
+    pub fn start_empty() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let state = Arc::new(Mutex::new(State::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let thread_state = Arc::clone(&state);
+        let thread_shutdown = Arc::clone(&shutdown);
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                if thread_shutdown.load(Ordering::SeqCst) {
+                    break;
+                }
+                if let Ok(stream) = stream {
+                    let state = Arc::clone(&thread_state);
+                    thread::spawn(move || {
+                        let _ = handle_connection(stream, &state);
+                    });
+                }
+            }
+        });
+
+        Ok(Self {
+            addr,
+            state,
+            shutdown,
+        })
+    }
+
+    /// The base URL of the server, suitable for
+    /// `InstanceMetadataClientBuilder::endpoint`.
+    pub fn endpoint(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// A client builder already pointed at this server.
+    pub fn client_builder(&self) -> InstanceMetadataClientBuilder {
+        InstanceMetadataClient::builder().endpoint(self.endpoint())
+    }
+
+    /// A client pointed at this server.
+    pub fn client(&self) -> InstanceMetadataClient {
+        self.client_builder().build()
+    }
+
+    /// A transport which answers from this server's values in-process,
+    /// without going over TCP.
+    ///
+    /// # Examples:
+    /// ```
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::InstanceMetadataClient;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let client = InstanceMetadataClient::builder()
+    ///     .transport(server.transport())
+    ///     .build();
+    /// assert_eq!(client.get().unwrap().instance_type, "t3.micro");
+    /// ```
+    pub fn transport(&self) -> MockTransport {
+        MockTransport {
+            state: Arc::clone(&self.state),
+        }
+    }
+
+    /// Serve `value` at `path`, relative to `/latest/`.
+    pub fn set(&self, path: &str, value: &str) {
+        lock(&self.state)
+            .values
+            .insert(path.trim_matches('/').to_string(), value.to_string());
+    }
+
+    /// Stop serving `path`; subsequent GETs return 404.
+    pub fn remove(&self, path: &str) {
+        let mut state = lock(&self.state);
+        let path = path.trim_matches('/');
+        state.values.remove(path);
+        state.statuses.remove(path);
+    }
+
+    /// Answer GETs for `path` with an empty response with `status`, e.g.
+    /// 503 or 429 to simulate an overloaded IMDS, until `remove()`d. A
+    /// status set for `api/token` applies to token requests.
+    ///
+    /// # Examples:
+    /// ```
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::Error;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set_status("meta-data/instance-id", 503);
+    /// let result = server.client().get_path("meta-data/instance-id");
+    /// assert!(matches!(result, Err(Error::HttpStatus(503, _))));
+    /// ```
+    pub fn set_status(&self, path: &str, status: u16) {
+        lock(&self.state)
+            .statuses
+            .insert(path.trim_matches('/').to_string(), status);
+    }
+
+    /// Answer GETs which don't carry a token, as IMDS does on instances
+    /// which still allow IMDSv1.
+    pub fn allow_imdsv1(&self, allowed: bool) {
+        lock(&self.state).imdsv1 = allowed;
+    }
+
+    /// Leave token requests unanswered while `dropped`, as IMDS appears to
+    /// when the instance's hop limit drops the response on its way to a
+    /// container. Requests time out on the client's side.
+    ///
+    /// # Examples:
+    /// ```
+    /// use std::time::Duration;
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::Error;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.drop_token_responses(true);
+    /// let client = server
+    ///     .client_builder()
+    ///     .timeout(Duration::from_millis(200))
+    ///     .max_attempts(1)
+    ///     .build();
+    ///
+    /// let error = client.get_path("meta-data/instance-id").unwrap_err();
+    /// assert!(matches!(error, Error::HopLimitExceeded(_)), "{:?}", error);
+    /// assert!(error.to_string().contains("--http-put-response-hop-limit 2"));
+    /// ```
+    pub fn drop_token_responses(&self, dropped: bool) {
+        lock(&self.state).drop_tokens = dropped;
+    }
+
+    /// Reject every token issued so far, as if they'd expired. Clients
+    /// get a 401 on their next request and have to fetch a new one.
+    ///
+    /// # Examples:
+    /// ```
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let client = server.client();
+    /// client.get().unwrap();
+    /// server.expire_tokens();
+    /// assert_eq!(client.get().unwrap().instance_id, "i-0123456789abcdef0");
+    /// ```
+    pub fn expire_tokens(&self) {
+        lock(&self.state).tokens.clear();
+    }
+
+    /// The number of HTTP requests served so far, including token requests.
+    pub fn request_count(&self) -> usize {
+        lock(&self.state).requests
+    }
+
+    /// The number of session tokens issued so far.
+    pub fn token_count(&self) -> usize {
+        lock(&self.state).issued
+    }
+
+    /// The number of TCP connections accepted so far. Connections are kept
+    /// alive between requests unless the client asks to close them.
+    pub fn connection_count(&self) -> usize {
+        lock(&self.state).connections
+    }
+}
+
+impl Drop for MockImdsServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the shutdown flag.
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+/// `MockTransport` serves requests from a `MockImdsServer`'s values
+/// without a network round trip. See `MockImdsServer::transport`.
+#[derive(Debug, Clone)]
+pub struct MockTransport {
+    state: Arc<Mutex<State>>,
+}
+
+impl ImdsTransport for MockTransport {
+    fn send(&self, request: &Request) -> Result<transport::Response> {
+        // Only the path matters; the scheme and host are whatever endpoint
+        // the client was configured with.
+        let target = match request.url.split_once("://") {
+            Some((_, rest)) => rest.find('/').map_or("/", |i| &rest[i..]),
+            None => request.url.as_str(),
+        };
+        let header = |name: &str| {
+            request
+                .headers
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, v)| v.as_str())
+        };
+
+        let response = {
+            let mut state = lock(&self.state);
+            state.requests += 1;
+            if drops(&state, request.method.as_str(), target) {
+                return Err(Error::Timeout("mock dropped the token response".into()));
+            }
+            respond(&mut state, request.method.as_str(), target, header)
+        };
+
+        Ok(transport::Response {
+            status: response.status,
+            headers: vec![("Content-Type".into(), "text/plain".into())],
+            body: response.body.into_bytes(),
+        })
+    }
+}
+
+struct Response {
+    status: u16,
+    reason: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn new(status: u16, reason: &'static str, body: impl Into<String>) -> Self {
+        Self {
+            status,
+            reason,
+            body: body.into(),
+        }
+    }
+}
+
+// Idle connections are closed after this long, so their threads don't
+// outlive the server by much.
+const IDLE_TIMEOUT: Duration = Duration::from_secs(5);
+
+fn handle_connection(stream: TcpStream, state: &Mutex<State>) -> io::Result<()> {
+    lock(state).connections += 1;
+    stream.set_read_timeout(Some(IDLE_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut stream = stream;
+
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line)? == 0 {
+            return Ok(());
+        }
+        let mut parts = request_line.split_whitespace();
+        let method = parts.next().unwrap_or_default().to_string();
+        let target = parts.next().unwrap_or_default().to_string();
+
+        let mut headers = Vec::new();
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 || line.trim().is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()));
+            }
+        }
+        let header = |name: &str| {
+            headers
+                .iter()
+                .find(|(n, _)| n == name)
+                .map(|(_, v)| v.as_str())
+        };
+
+        // Skip any body so the next request on the connection can be read.
+        let length = header("content-length").and_then(|n| n.parse().ok());
+        io::copy(
+            &mut (&mut reader).take(length.unwrap_or(0)),
+            &mut io::sink(),
+        )?;
+        let close = header("connection").is_some_and(|c| c.eq_ignore_ascii_case("close"));
+
+        let response = {
+            let mut state = lock(state);
+            state.requests += 1;
+            if drops(&state, &method, &target) {
+                None
+            } else {
+                Some(respond(&mut state, &method, &target, header))
+            }
+        };
+        // Hold the connection open without answering until the client
+        // gives up on it.
+        let response = match response {
+            Some(response) => response,
+            None => {
+                let _ = io::copy(&mut reader, &mut io::sink());
+                return Ok(());
+            }
+        };
+
+        // Written in one go; a response split over several writes would
+        // sit behind Nagle's algorithm on a kept-alive connection.
+        let response = format!(
+            "HTTP/1.1 {} {}\r\nContent-Type: text/plain\r\nContent-Length: {}\r\nConnection: {}\r\n\r\n{}",
+            response.status,
+            response.reason,
+            response.body.len(),
+            if close { "close" } else { "keep-alive" },
+            response.body
+        );
+        stream.write_all(response.as_bytes())?;
+        if close {
+            return Ok(());
+        }
+    }
+}
+
+fn respond<'a>(
+    state: &mut State,
+    method: &str,
+    target: &str,
+    header: impl Fn(&str) -> Option<&'a str>,
+) -> Response {
+    let (version, path) = match target.trim_start_matches('/').split_once('/') {
+        Some((version, path)) => (version, path),
+        None => (target.trim_start_matches('/'), ""),
+    };
+
+    if version == "latest" && path == "api/token" {
+        if let Some(&status) = state.statuses.get(path) {
+            return Response::new(status, reason(status), "");
+        }
+        if method != "PUT" {
+            return Response::new(405, "Method Not Allowed", "");
+        }
+        if header("x-aws-ec2-metadata-token-ttl-seconds").is_none() {
+            return Response::new(400, "Bad Request", "");
+        }
+        let token = format!("mock-token-{}", state.issued);
+        state.issued += 1;
+        state.tokens.insert(token.clone());
+        return Response::new(200, "OK", token);
+    }
+
+    if method != "GET" {
+        return Response::new(405, "Method Not Allowed", "");
+    }
+    match header("x-aws-ec2-metadata-token") {
+        Some(token) if state.tokens.contains(token) => {}
+        None if state.imdsv1 => {}
+        _ => return Response::new(401, "Unauthorized", ""),
+    }
+
+    if version.is_empty() {
+        return Response::new(200, "OK", API_VERSIONS.join("\n"));
+    }
+    if !API_VERSIONS.contains(&version) {
+        return Response::new(404, "Not Found", "");
+    }
+
+    let path = path.trim_matches('/');
+    if let Some(&status) = state.statuses.get(path) {
+        return Response::new(status, reason(status), "");
+    }
+    if let Some(value) = state.values.get(path) {
+        return Response::new(200, "OK", value.clone());
+    }
+
+    match listing(&state.values, path) {
+        Some(listing) => Response::new(200, "OK", listing),
+        None => Response::new(404, "Not Found", ""),
+    }
+}
+
+// Whether the request is a token request whose response is to be dropped.
+fn drops(state: &State, method: &str, target: &str) -> bool {
+    state.drop_tokens && method == "PUT" && target.trim_end_matches('/') == "/latest/api/token"
+}
+
+fn reason(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    }
+}
+
+// IMDS answers a GET on a directory with one child per line, with a
+// trailing slash on children which are themselves directories.
+fn listing(values: &BTreeMap<String, String>, dir: &str) -> Option<String> {
+    let prefix = if dir.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", dir)
+    };
+
+    let children: BTreeSet<String> = values
+        .keys()
+        .filter_map(|key| key.strip_prefix(prefix.as_str()))
+        .map(|rest| match rest.split_once('/') {
+            Some((child, _)) => format!("{}/", child),
+            None => rest.to_string(),
+        })
+        .collect();
+
+    if children.is_empty() {
+        None
+    } else {
+        Some(children.into_iter().collect::<Vec<_>>().join("\n"))
+    }
+}
@@ -0,0 +1,163 @@
+use std::collections::BTreeMap;
+
+#[cfg(feature = "json")]
+use serde::Serialize;
+
+use crate::{missing_as_none, Error, InstanceMetadataClient, Result};
+
+const MACS: &str = "meta-data/network/interfaces/macs/";
+pub(crate) const MAC: &str = "meta-data/mac";
+pub(crate) const SECURITY_GROUPS: &str = "meta-data/security-groups";
+// What `get()` reads from the primary interface, by name under its path.
+pub(crate) const PRIMARY_INTERFACE_FIELDS: &[&str] = &["security-group-ids", "vpc-id", "subnet-id"];
+
+/// `NetworkInterface` describes one of the instance's network interfaces,
+/// from `network/interfaces/macs/<mac>/`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct NetworkInterface {
+    /// The interface's MAC address, which IMDS uses to key it
+    pub mac: String,
+
+    /// e.g. `eni-0123456789abcdef0` - always available in a VPC
+    pub interface_id: Option<String>,
+
+    /// The interface's position on the instance; the primary interface is 0
+    pub device_number: Option<u32>,
+
+    /// e.g. `vpc-0123456789abcdef0`
+    pub vpc_id: Option<String>,
+
+    /// e.g. `subnet-0123456789abcdef0`
+    pub subnet_id: Option<String>,
+
+    /// The account that owns the interface. Differs from the instance's
+    /// for interfaces attached by another service, e.g. EKS.
+    pub owner_id: Option<String>,
+
+    /// Private IPv4 addresses, primary first
+    pub local_ipv4s: Vec<String>,
+
+    /// Public and Elastic IPv4 addresses
+    pub public_ipv4s: Vec<String>,
+
+    /// Which private address each public address maps to, keyed by the
+    /// public address
+    pub ipv4_associations: BTreeMap<String, String>,
+
+    /// e.g. `sg-0123456789abcdef0`
+    pub security_group_ids: Vec<String>,
+}
+
+impl NetworkInterface {
+    /// The public or Elastic IP address associated with `private_ip`, if
+    /// there is one.
+    pub fn public_ip_for(&self, private_ip: &str) -> Option<&str> {
+        self.ipv4_associations
+            .iter()
+            .find(|(_, private)| *private == private_ip)
+            .map(|(public, _)| public.as_str())
+    }
+}
+
+// The path of `name` under the interface with MAC address `mac`.
+pub(crate) fn interface_path(mac: &str, name: &str) -> String {
+    format!("{}{}/{}", MACS, mac.trim(), name)
+}
+
+// IMDS lists multiple values one per line.
+pub(crate) fn lines(value: &str) -> Vec<String> {
+    value
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl InstanceMetadataClient {
+    /// Get every network interface attached to the instance, ordered by
+    /// MAC address.
+    pub fn get_network_interfaces(&self) -> Result<Vec<NetworkInterface>> {
+        let token = self.get_token()?;
+        lines(&self.fetch(MACS, &token)?)
+            .iter()
+            .map(|mac| self.fetch_network_interface(mac.trim_end_matches('/'), &token))
+            .collect()
+    }
+
+    /// Get the instance's primary network interface, the one it booted
+    /// with, from the MAC address at `meta-data/mac`.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let client = server.client();
+    /// let eni = client.get_primary_network_interface().unwrap();
+    /// assert_eq!(eni.device_number, Some(0));
+    ///
+    /// let metadata = client.get().unwrap();
+    /// assert_eq!(Some(eni.mac), metadata.primary_mac);
+    /// assert_eq!(eni.subnet_id, metadata.subnet_id);
+    /// assert_eq!(metadata.vpc_id.as_deref(), Some("vpc-0123456789abcdef0"));
+    /// # }
+    /// ```
+    pub fn get_primary_network_interface(&self) -> Result<NetworkInterface> {
+        let token = self.get_token()?;
+        let mac = self.fetch(MAC, &token)?;
+        self.fetch_network_interface(mac.trim(), &token)
+    }
+
+    /// Get the network interface with MAC address `mac`.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let eni = server.client().get_network_interface("0e:49:61:0f:c3:11").unwrap();
+    /// assert_eq!(eni.interface_id.as_deref(), Some("eni-0123456789abcdef0"));
+    /// assert_eq!(eni.public_ip_for("10.0.0.1"), Some("203.0.113.1"));
+    /// # }
+    /// ```
+    pub fn get_network_interface(&self, mac: &str) -> Result<NetworkInterface> {
+        let token = self.get_token()?;
+        self.fetch_network_interface(mac, &token)
+    }
+
+    fn fetch_network_interface(&self, mac: &str, token: &str) -> Result<NetworkInterface> {
+        let optional = |name: &str| missing_as_none(self.fetch(&interface_path(mac, name), token));
+        let list =
+            |name: &str| -> Result<Vec<String>> { Ok(lines(&optional(name)?.unwrap_or_default())) };
+
+        let device_number = match optional("device-number")? {
+            Some(n) => Some(n.trim().parse().map_err(|_| {
+                let path = interface_path(mac, "device-number");
+                Error::InvalidValue(format!("{}: {:?}", path, n))
+            })?),
+            None => None,
+        };
+
+        let mut ipv4_associations = BTreeMap::new();
+        for public_ip in list("ipv4-associations/")? {
+            let path = interface_path(mac, &format!("ipv4-associations/{}", public_ip));
+            let private_ip = self.fetch(&path, token)?;
+            ipv4_associations.insert(public_ip, private_ip.trim().to_string());
+        }
+
+        Ok(NetworkInterface {
+            mac: mac.to_string(),
+            interface_id: optional("interface-id")?,
+            device_number,
+            vpc_id: optional("vpc-id")?.map(|id| id.trim().to_string()),
+            subnet_id: optional("subnet-id")?.map(|id| id.trim().to_string()),
+            owner_id: optional("owner-id")?,
+            local_ipv4s: list("local-ipv4s")?,
+            public_ipv4s: list("public-ipv4s")?,
+            ipv4_associations,
+            security_group_ids: list("security-group-ids")?,
+        })
+    }
+}
@@ -0,0 +1,48 @@
+//! Conversion of `InstanceMetadata` into OpenTelemetry resource
+//! attributes, enabled by the `opentelemetry` feature. Attribute names
+//! follow the cloud and host semantic conventions:
+//! https://opentelemetry.io/docs/specs/semconv/resource/cloud/
+
+use opentelemetry::KeyValue;
+
+use crate::InstanceMetadata;
+
+impl InstanceMetadata {
+    /// The metadata as OpenTelemetry resource attributes, ready for
+    /// `Resource::builder().with_attributes(..)`.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use opentelemetry::{Key, Value};
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let attributes = server.client().get().unwrap().to_otel_attributes();
+    /// let region = attributes.iter().find(|kv| kv.key == Key::from_static_str("cloud.region"));
+    /// assert_eq!(region.unwrap().value, Value::from("us-east-1"));
+    /// # }
+    /// ```
+    pub fn to_otel_attributes(&self) -> Vec<KeyValue> {
+        let mut attributes = vec![
+            KeyValue::new("cloud.provider", "aws"),
+            KeyValue::new("cloud.platform", "aws_ec2"),
+            KeyValue::new("cloud.region", self.region.to_string()),
+            KeyValue::new(
+                "cloud.availability_zone",
+                self.availability_zone.to_string(),
+            ),
+        ];
+        if let Some(account_id) = &self.account_id {
+            attributes.push(KeyValue::new("cloud.account.id", account_id.to_string()));
+        }
+        attributes.push(KeyValue::new("host.id", self.instance_id.to_string()));
+        attributes.push(KeyValue::new("host.type", self.instance_type.to_string()));
+        if let Some(hostname) = &self.hostname {
+            attributes.push(KeyValue::new("host.name", hostname.clone()));
+        }
+        if let Some(ami_id) = &self.ami_id {
+            attributes.push(KeyValue::new("host.image.id", ami_id.to_string()));
+        }
+        attributes
+    }
+}
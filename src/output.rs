@@ -0,0 +1,160 @@
+use std::collections::BTreeMap;
+use std::fmt;
+
+use crate::InstanceMetadata;
+
+impl InstanceMetadata {
+    /// The metadata as `(key, value)` pairs with kebab-case keys, in the
+    /// order they're printed in. Fields IMDS didn't serve are left out.
+    pub fn fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = vec![("instance-id", self.instance_id.to_string())];
+        if let Some(account_id) = &self.account_id {
+            fields.push(("account-id", account_id.to_string()));
+        }
+        if let Some(ami_id) = &self.ami_id {
+            fields.push(("ami-id", ami_id.to_string()));
+        }
+        fields.push(("instance-type", self.instance_type.to_string()));
+        if let Some(life_cycle) = &self.instance_life_cycle {
+            fields.push(("instance-life-cycle", life_cycle.to_string()));
+        }
+        fields.push(("region", self.region.to_string()));
+        fields.push(("partition", self.region.partition().to_string()));
+        fields.push(("availability-zone", self.availability_zone.to_string()));
+
+        let placement = &self.placement;
+        if let Some(zone_id) = &placement.availability_zone_id {
+            fields.push(("availability-zone-id", zone_id.clone()));
+        }
+        if let Some(group_name) = &placement.group_name {
+            fields.push(("placement-group", group_name.clone()));
+        }
+        if let Some(partition) = placement.partition_number {
+            fields.push(("partition-number", partition.to_string()));
+        }
+        if let Some(host_id) = &placement.host_id {
+            fields.push(("host-id", host_id.clone()));
+        }
+        if let Some(outpost_arn) = &placement.outpost_arn {
+            fields.push(("outpost-arn", outpost_arn.clone()));
+        }
+
+        if let Some(hostname) = &self.hostname {
+            fields.push(("hostname", hostname.clone()));
+        }
+        if let Some(local_hostname) = &self.local_hostname {
+            fields.push(("local-hostname", local_hostname.clone()));
+        }
+        if let Some(public_hostname) = &self.public_hostname {
+            fields.push(("public-hostname", public_hostname.clone()));
+        }
+        if let Some(public_ipv4) = &self.public_ipv4 {
+            fields.push(("public-ipv4", public_ipv4.clone()));
+        }
+        if !self.security_groups.is_empty() {
+            fields.push(("security-groups", self.security_groups.join(",")));
+        }
+        if !self.security_group_ids.is_empty() {
+            fields.push(("security-group-ids", self.security_group_ids.join(",")));
+        }
+        if let Some(mac) = &self.primary_mac {
+            fields.push(("primary-mac", mac.clone()));
+        }
+        if let Some(vpc_id) = &self.vpc_id {
+            fields.push(("vpc-id", vpc_id.clone()));
+        }
+        if let Some(subnet_id) = &self.subnet_id {
+            fields.push(("subnet-id", subnet_id.clone()));
+        }
+        if !self.tags.is_empty() {
+            let tags: Vec<String> = self
+                .tags
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect();
+            fields.push(("tags", tags.join(",")));
+        }
+
+        fields
+    }
+
+    /// The metadata as pretty-printed JSON, with the same field names as
+    /// the struct.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> crate::Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// The metadata as environment variables: each field's name in upper
+    /// case, with underscores and an `EC2_` prefix, e.g. `EC2_INSTANCE_ID`.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let env = server.client().get().unwrap().to_env_map();
+    /// assert_eq!(env["EC2_REGION"], "us-east-1");
+    /// assert_eq!(env["EC2_INSTANCE_ID"], "i-0123456789abcdef0");
+    /// # }
+    /// ```
+    pub fn to_env_map(&self) -> BTreeMap<String, String> {
+        self.fields()
+            .into_iter()
+            .map(|(key, value)| {
+                let name = format!("EC2_{}", key.replace('-', "_").to_ascii_uppercase());
+                (name, value)
+            })
+            .collect()
+    }
+
+    /// The metadata as a two-column table with aligned columns, one row
+    /// per field.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let table = server.client().get().unwrap().to_pretty_table();
+    /// assert!(table.contains("| instance-type        | t3.micro "));
+    /// # }
+    /// ```
+    pub fn to_pretty_table(&self) -> String {
+        let fields = self.fields();
+        let key_width = fields.iter().map(|(k, _)| k.len()).max().unwrap_or(0);
+        let value_width = fields.iter().map(|(_, v)| v.len()).max().unwrap_or(0);
+        let border = format!(
+            "+{}+{}+",
+            "-".repeat(key_width + 2),
+            "-".repeat(value_width + 2)
+        );
+
+        let mut table = border.clone();
+        for (key, value) in &fields {
+            table.push_str(&format!(
+                "\n| {:kw$} | {:vw$} |",
+                key,
+                value,
+                kw = key_width,
+                vw = value_width
+            ));
+        }
+        table.push('\n');
+        table.push_str(&border);
+        table
+    }
+}
+
+/// One `key: value` line per field, as printed by `ec2-metadata all`.
+impl fmt::Display for InstanceMetadata {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, (key, value)) in self.fields().iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}: {}", key, value)?;
+        }
+        Ok(())
+    }
+}
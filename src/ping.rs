@@ -0,0 +1,80 @@
+use std::time::Duration;
+
+use crate::transport::{Method, Request};
+use crate::InstanceMetadataClient;
+
+const PING_TIMEOUT_MS: u64 = 250;
+
+/// `Availability` is what `InstanceMetadataClient::ping` found out about
+/// IMDS.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Availability {
+    /// IMDSv2 works. `imdsv2_required` is true if requests without a
+    /// token are rejected, i.e. the instance has `HttpTokens=required`.
+    Available { imdsv2_required: bool },
+    /// IMDS answers, but only to IMDSv1 requests; token requests fail.
+    V1Only,
+    /// Requests without a token are rejected, and token requests don't
+    /// get an answer, which is what the metadata hop limit does to
+    /// requests from containers. See `Error::HopLimitExceeded`.
+    HopLimitExceeded,
+    /// Nothing answered, or access is disabled.
+    Unreachable,
+}
+
+impl Availability {
+    /// True if a client can fetch metadata.
+    pub fn is_available(self) -> bool {
+        matches!(self, Availability::Available { .. } | Availability::V1Only)
+    }
+}
+
+impl InstanceMetadataClient {
+    /// Check whether IMDS is reachable and which versions it accepts,
+    /// with a short (250ms) timeout per request. Doesn't retry. Meant for
+    /// startup checks before the first `get()`.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::Availability;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let availability = server.client().ping();
+    /// assert_eq!(availability, Availability::Available { imdsv2_required: true });
+    /// # }
+    /// ```
+    pub fn ping(&self) -> Availability {
+        self.ping_with_timeout(Duration::from_millis(PING_TIMEOUT_MS))
+    }
+
+    /// Same as `ping()`, with a custom timeout for each request.
+    pub fn ping_with_timeout(&self, timeout: Duration) -> Availability {
+        if self.inner.disabled {
+            return Availability::Unreachable;
+        }
+
+        let token_request = self.token_request().timeout(timeout);
+        let token = self.send(&token_request, "api/token");
+
+        let probe = Request::new(Method::Get, format!("{}meta-data/", self.inner.base_url))
+            .timeout(timeout);
+        let probe_status = self.send(&probe, "meta-data/").ok().map(|resp| resp.status);
+
+        match token {
+            Ok(resp) if resp.is_success() => {
+                if let Ok(token) = resp.text() {
+                    self.inner.token.put(token);
+                }
+                Availability::Available {
+                    imdsv2_required: probe_status == Some(401),
+                }
+            }
+            _ if probe_status.is_some_and(|status| (200..300).contains(&status)) => {
+                Availability::V1Only
+            }
+            Err(_) if probe_status == Some(401) => Availability::HopLimitExceeded,
+            _ => Availability::Unreachable,
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::time::Instant;
+
+#[cfg(feature = "json")]
+use serde::{Deserialize, Serialize};
+
+use crate::{missing_as_none, Error, InstanceMetadataClient, Result};
+
+const AVAILABILITY_ZONE_ID: &str = "meta-data/placement/availability-zone-id";
+const REGION: &str = "meta-data/placement/region";
+const GROUP_NAME: &str = "meta-data/placement/group-name";
+const PARTITION_NUMBER: &str = "meta-data/placement/partition-number";
+const HOST_ID: &str = "meta-data/placement/host-id";
+const OUTPOST_ARN: &str = "meta-data/placement/outpost-arn";
+
+// Every path a `Placement` is read from.
+pub(crate) const PLACEMENT_PATHS: &[&str] = &[
+    AVAILABILITY_ZONE_ID,
+    REGION,
+    GROUP_NAME,
+    PARTITION_NUMBER,
+    HOST_ID,
+    OUTPOST_ARN,
+];
+
+/// `Placement` holds where the instance runs, beyond its availability
+/// zone. Fields are `None` when IMDS doesn't serve them for the instance.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "json", derive(Serialize, Deserialize))]
+pub struct Placement {
+    /// Zone id, consistent across accounts (e.g. `use1-az4`)
+    pub availability_zone_id: Option<String>,
+
+    /// Region as reported by IMDS (e.g. `us-east-1`)
+    pub region: Option<String>,
+
+    /// Placement group name - only for instances in a placement group
+    pub group_name: Option<String>,
+
+    /// Partition number - only for instances in a partition placement group
+    pub partition_number: Option<u32>,
+
+    /// Dedicated Host id - only for instances on a Dedicated Host
+    pub host_id: Option<String>,
+
+    /// e.g. `arn:aws:outposts:us-west-2:123456789012:outpost/op-0123456789abcdef0`
+    /// - only for instances on an Outpost
+    pub outpost_arn: Option<String>,
+}
+
+impl Placement {
+    // `optional` returns the value at one of `PLACEMENT_PATHS`, or `None`
+    // if IMDS doesn't serve it. A partition number which isn't a number is
+    // an error, unless `lenient`, when it's left out.
+    pub(crate) fn from_values(
+        optional: impl Fn(&'static str) -> Result<Option<String>>,
+        lenient: bool,
+    ) -> Result<Self> {
+        let partition_number = match optional(PARTITION_NUMBER)? {
+            Some(n) => match n.trim().parse() {
+                Ok(n) => Some(n),
+                Err(_) if lenient => None,
+                Err(_) => {
+                    return Err(Error::InvalidValue(format!(
+                        "{}: {:?}",
+                        PARTITION_NUMBER, n
+                    )))
+                }
+            },
+            None => None,
+        };
+
+        Ok(Placement {
+            availability_zone_id: optional(AVAILABILITY_ZONE_ID)?,
+            region: optional(REGION)?,
+            group_name: optional(GROUP_NAME)?,
+            partition_number,
+            host_id: optional(HOST_ID)?,
+            outpost_arn: optional(OUTPOST_ARN)?.map(|arn| arn.trim().to_string()),
+        })
+    }
+
+    /// The Outpost id (e.g. `op-0123456789abcdef0`), from the Outpost ARN.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::ZoneKind;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set(
+    ///     "meta-data/placement/outpost-arn",
+    ///     "arn:aws:outposts:us-east-1:123456789012:outpost/op-0123456789abcdef0",
+    /// );
+    ///
+    /// let metadata = server.client().get().unwrap();
+    /// assert_eq!(metadata.availability_zone.kind(), ZoneKind::Outpost);
+    /// assert_eq!(metadata.placement.outpost_id(), Some("op-0123456789abcdef0"));
+    /// # }
+    /// ```
+    pub fn outpost_id(&self) -> Option<&str> {
+        let arn = self.outpost_arn.as_deref()?;
+        arn.rsplit_once(":outpost/").map(|(_, id)| id)
+    }
+
+    /// True if the instance runs on an Outpost.
+    pub fn is_on_outpost(&self) -> bool {
+        self.outpost_arn.is_some()
+    }
+}
+
+impl InstanceMetadataClient {
+    pub(crate) fn fetch_placement(
+        &self,
+        token: &str,
+        deadline: Option<Instant>,
+    ) -> Result<Placement> {
+        Placement::from_values(
+            |path| missing_as_none(self.fetch_within(path, token, deadline)),
+            self.inner.lenient,
+        )
+    }
+
+    /// Get the placement metadata for the machine.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::Error;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set("meta-data/placement/group-name", "analytics");
+    /// server.set("meta-data/placement/partition-number", "3");
+    ///
+    /// let placement = server.client().get_placement().unwrap();
+    /// assert_eq!(placement.availability_zone_id.as_deref(), Some("use1-az4"));
+    /// assert_eq!(placement.region.as_deref(), Some("us-east-1"));
+    /// assert_eq!(placement.group_name.as_deref(), Some("analytics"));
+    /// assert_eq!(placement.partition_number, Some(3));
+    /// // Only instances on a Dedicated Host have one.
+    /// assert_eq!(placement.host_id, None);
+    ///
+    /// server.set("meta-data/placement/partition-number", "three");
+    /// let error = server.client().get_placement().unwrap_err();
+    /// assert!(matches!(error, Error::InvalidValue(_)), "{:?}", error);
+    /// # }
+    /// ```
+    pub fn get_placement(&self) -> Result<Placement> {
+        let token = self.get_token()?;
+        self.fetch_placement(&token, None)
+    }
+}
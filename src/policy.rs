@@ -0,0 +1,107 @@
+use std::collections::BTreeSet;
+
+use crate::{Error, Result};
+
+/// The `InstanceMetadata` fields IMDS can legitimately leave out or refuse
+/// to serve, e.g. because the category is blocked by the instance's
+/// metadata options. The instance id, availability zone and instance type
+/// are always required.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MetadataField {
+    AccountId,
+    AmiId,
+    Hostname,
+    LocalHostname,
+    PublicHostname,
+    PublicIpv4,
+    InstanceLifeCycle,
+    PrimaryMac,
+    VpcId,
+    SubnetId,
+    /// Left empty rather than `None` when it's not required
+    SecurityGroups,
+    /// Left empty rather than `None` when it's not required
+    SecurityGroupIds,
+    /// Left empty rather than `None` when it's not required
+    Tags,
+}
+
+/// `FieldPolicy` decides which fields `get()` fails without. A field that
+/// isn't required is `None` if IMDS doesn't serve it, answers with an
+/// error status, or serves something that can't be parsed; connection
+/// failures and timeouts still fail `get()`.
+///
+/// By default the account id, AMI id and both hostnames are required.
+///
+/// # Examples:
+/// ```
+/// # #[cfg(feature = "mock")] {
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// use ec2_instance_metadata::{FieldPolicy, MetadataField};
+/// let server = MockImdsServer::start().unwrap();
+/// server.set_status("dynamic/instance-identity/document", 403);
+/// server.set_status("meta-data/identity-credentials/ec2/info", 403);
+/// assert!(server.client().get().is_err());
+///
+/// let client = server
+///     .client_builder()
+///     .field_policy(FieldPolicy::default().allow_missing(MetadataField::AccountId))
+///     .build();
+/// assert_eq!(client.get().unwrap().account_id, None);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldPolicy {
+    required: BTreeSet<MetadataField>,
+}
+
+impl FieldPolicy {
+    /// A policy requiring none of the fields.
+    pub fn none() -> Self {
+        Self {
+            required: BTreeSet::new(),
+        }
+    }
+
+    /// Fail `get()` if `field` can't be fetched.
+    pub fn require(mut self, field: MetadataField) -> Self {
+        self.required.insert(field);
+        self
+    }
+
+    /// Leave `field` out if it can't be fetched.
+    pub fn allow_missing(mut self, field: MetadataField) -> Self {
+        self.required.remove(&field);
+        self
+    }
+
+    pub fn is_required(&self, field: MetadataField) -> bool {
+        self.required.contains(&field)
+    }
+
+    // Demotes a failure to fetch an optional field to `None`.
+    pub(crate) fn apply<T>(&self, field: MetadataField, value: Result<T>) -> Result<Option<T>> {
+        match value {
+            Ok(value) => Ok(Some(value)),
+            Err(Error::NotFound(_))
+            | Err(Error::HttpStatus(..))
+            | Err(Error::JsonError(_))
+            | Err(Error::InvalidValue(_))
+                if !self.is_required(field) =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
+    }
+}
+
+impl Default for FieldPolicy {
+    fn default() -> Self {
+        Self::none()
+            .require(MetadataField::AccountId)
+            .require(MetadataField::AmiId)
+            .require(MetadataField::Hostname)
+            .require(MetadataField::LocalHostname)
+    }
+}
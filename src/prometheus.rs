@@ -0,0 +1,56 @@
+//! Rendering of `InstanceMetadata` as a Prometheus info metric, enabled by
+//! the `prometheus` feature, so exporters can attach the instance's
+//! identity to their other metrics with a join on its labels:
+//! https://prometheus.io/docs/instrumenting/exposition_formats/
+
+use crate::InstanceMetadata;
+
+const INFO_METRIC: &str = "ec2_instance_info";
+
+impl InstanceMetadata {
+    /// The metadata in the Prometheus text format, as an
+    /// `ec2_instance_info` gauge which is always 1, labelled with each of
+    /// `fields()` under its name in snake case.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let text = server.client().get().unwrap().to_prometheus();
+    /// let sample = text.lines().last().unwrap();
+    /// assert!(sample.starts_with(r#"ec2_instance_info{instance_id="i-0123456789abcdef0","#));
+    /// assert!(sample.contains(r#",region="us-east-1","#));
+    /// assert!(sample.ends_with("} 1"));
+    /// # }
+    /// ```
+    pub fn to_prometheus(&self) -> String {
+        let labels: Vec<String> = self
+            .fields()
+            .iter()
+            .map(|(name, value)| format!("{}=\"{}\"", name.replace('-', "_"), escape(value)))
+            .collect();
+        format!(
+            "# HELP {metric} EC2 instance metadata, as labels.\n\
+             # TYPE {metric} gauge\n\
+             {metric}{{{labels}}} 1\n",
+            metric = INFO_METRIC,
+            labels = labels.join(","),
+        )
+    }
+}
+
+// Label values are quoted, so backslashes, quotes and newlines in them
+// must be escaped.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
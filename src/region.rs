@@ -0,0 +1,306 @@
+use std::borrow::Cow;
+use std::str::FromStr;
+
+#[cfg(feature = "json")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{AvailabilityZone, Error, InstanceMetadataClient, Result};
+
+pub(crate) const SERVICES_PARTITION: &str = "meta-data/services/partition";
+const SERVICES_DOMAIN: &str = "meta-data/services/domain";
+
+/// `Partition` is the group of regions a region belongs to. Each partition
+/// has its own endpoints, ARN prefix, and credentials.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Partition {
+    /// Commercial regions
+    Aws,
+    /// China regions
+    AwsCn,
+    /// AWS GovCloud (US)
+    AwsUsGov,
+    /// US ISO regions
+    AwsIso,
+    /// US ISOB regions
+    AwsIsoB,
+}
+
+impl Partition {
+    /// The partition id used in ARNs, e.g. `aws-us-gov`.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Partition::Aws => "aws",
+            Partition::AwsCn => "aws-cn",
+            Partition::AwsUsGov => "aws-us-gov",
+            Partition::AwsIso => "aws-iso",
+            Partition::AwsIsoB => "aws-iso-b",
+        }
+    }
+}
+
+impl FromStr for Partition {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s.trim() {
+            "aws" => Ok(Partition::Aws),
+            "aws-cn" => Ok(Partition::AwsCn),
+            "aws-us-gov" => Ok(Partition::AwsUsGov),
+            "aws-iso" => Ok(Partition::AwsIso),
+            "aws-iso-b" => Ok(Partition::AwsIsoB),
+            other => Err(Error::UnknownPartition(other.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for Partition {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.as_str())
+    }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for Partition {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+pub(crate) const REGIONS: &[(&str, Partition)] = &[
+    ("af-south-1", Partition::Aws),
+    ("ap-east-1", Partition::Aws),
+    ("ap-east-2", Partition::Aws),
+    ("ap-northeast-1", Partition::Aws),
+    ("ap-northeast-2", Partition::Aws),
+    ("ap-northeast-3", Partition::Aws),
+    ("ap-south-1", Partition::Aws),
+    ("ap-south-2", Partition::Aws),
+    ("ap-southeast-1", Partition::Aws),
+    ("ap-southeast-2", Partition::Aws),
+    ("ap-southeast-3", Partition::Aws),
+    ("ap-southeast-4", Partition::Aws),
+    ("ap-southeast-5", Partition::Aws),
+    ("ap-southeast-6", Partition::Aws),
+    ("ap-southeast-7", Partition::Aws),
+    ("ca-central-1", Partition::Aws),
+    ("ca-west-1", Partition::Aws),
+    ("eu-central-1", Partition::Aws),
+    ("eu-central-2", Partition::Aws),
+    ("eu-north-1", Partition::Aws),
+    ("eu-south-1", Partition::Aws),
+    ("eu-south-2", Partition::Aws),
+    ("eu-west-1", Partition::Aws),
+    ("eu-west-2", Partition::Aws),
+    ("eu-west-3", Partition::Aws),
+    ("il-central-1", Partition::Aws),
+    ("me-central-1", Partition::Aws),
+    ("me-south-1", Partition::Aws),
+    ("mx-central-1", Partition::Aws),
+    ("sa-east-1", Partition::Aws),
+    ("us-east-1", Partition::Aws),
+    ("us-east-2", Partition::Aws),
+    ("us-west-1", Partition::Aws),
+    ("us-west-2", Partition::Aws),
+    ("cn-north-1", Partition::AwsCn),
+    ("cn-northwest-1", Partition::AwsCn),
+    ("us-gov-east-1", Partition::AwsUsGov),
+    ("us-gov-west-1", Partition::AwsUsGov),
+    ("us-iso-east-1", Partition::AwsIso),
+    ("us-iso-west-1", Partition::AwsIso),
+    ("us-isob-east-1", Partition::AwsIsoB),
+];
+
+// The partition a region this crate doesn't list most likely belongs to,
+// going by its name.
+fn partition_of(name: &str) -> Partition {
+    if name.starts_with("cn-") {
+        Partition::AwsCn
+    } else if name.starts_with("us-gov-") {
+        Partition::AwsUsGov
+    } else if name.starts_with("us-isob-") {
+        Partition::AwsIsoB
+    } else if name.starts_with("us-iso-") {
+        Partition::AwsIso
+    } else {
+        Partition::Aws
+    }
+}
+
+// True for names shaped like a region's, e.g. `us-east-1` or
+// `ap-southeast-7`, whether or not this crate lists them.
+#[cfg(feature = "json")]
+pub(crate) fn looks_like_region(name: &str) -> bool {
+    let parts: Vec<&str> = name.split('-').collect();
+    match parts.as_slice() {
+        [geo, rest @ .., number] if !rest.is_empty() => {
+            geo.len() >= 2
+                && geo.bytes().all(|b| b.is_ascii_lowercase())
+                && rest
+                    .iter()
+                    .all(|part| !part.is_empty() && part.bytes().all(|b| b.is_ascii_lowercase()))
+                && !number.is_empty()
+                && number.bytes().all(|b| b.is_ascii_digit())
+        }
+        _ => false,
+    }
+}
+
+/// `Region` is an AWS region along with the partition it belongs to.
+///
+/// Parsing only accepts the regions this crate lists. `get()` builds the
+/// region from what IMDS reports instead, so regions launched after this
+/// release still work.
+///
+/// # Examples:
+/// ```
+/// use ec2_instance_metadata::{Partition, Region};
+/// let region: Region = "us-gov-west-1".parse().unwrap();
+/// assert_eq!(region.partition(), Partition::AwsUsGov);
+/// assert_eq!(region, "us-gov-west-1");
+///
+/// let region = Region::new("eu-east-9", Partition::Aws);
+/// assert_eq!(region.name(), "eu-east-9");
+/// ```
+///
+/// A region missing from this crate's list, along with the partition IMDS
+/// reports for it:
+/// ```
+/// # #[cfg(feature = "mock")] {
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// use ec2_instance_metadata::{Partition, ZoneKind};
+/// let server = MockImdsServer::start().unwrap();
+/// server.set("meta-data/placement/region", "eu-central-3");
+/// server.set("meta-data/placement/availability-zone", "eu-central-3a");
+/// server.set("meta-data/services/partition", "aws");
+/// assert!("eu-central-3".parse::<ec2_instance_metadata::Region>().is_err());
+///
+/// let metadata = server.client().get().unwrap();
+/// assert_eq!(metadata.region, "eu-central-3");
+/// assert_eq!(metadata.region.partition(), Partition::Aws);
+/// assert_eq!(metadata.availability_zone.region(), "eu-central-3");
+/// assert_eq!(metadata.availability_zone.kind(), ZoneKind::Standard);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Region {
+    name: Cow<'static, str>,
+    partition: Partition,
+}
+
+impl Region {
+    /// A region this crate may not list, such as one launched after this
+    /// release.
+    pub fn new(name: impl Into<String>, partition: Partition) -> Region {
+        Region {
+            name: Cow::Owned(name.into()),
+            partition,
+        }
+    }
+
+    // The region IMDS reports, with the partition IMDS reports if it
+    // served one. The table is only consulted when it didn't, and the
+    // name only for regions missing from the table too.
+    pub(crate) fn reported(name: &str, partition: Option<Partition>) -> Region {
+        let name = name.trim();
+        let listed = REGIONS.iter().find(|(listed, _)| *listed == name);
+        let partition = partition
+            .or_else(|| listed.map(|&(_, partition)| partition))
+            .unwrap_or_else(|| partition_of(name));
+        match listed {
+            Some(&(name, _)) => Region {
+                name: Cow::Borrowed(name),
+                partition,
+            },
+            None => Region::new(name, partition),
+        }
+    }
+
+    /// The region name, e.g. `us-east-1`.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn partition(&self) -> Partition {
+        self.partition
+    }
+
+    /// Work out the region from an availability zone name such as
+    /// `us-east-1a`, a Local Zone such as `us-east-1-bos-1a`, or a
+    /// Wavelength Zone such as `us-east-1-wl1-atl-wlz-1`.
+    pub fn from_availability_zone(availability_zone: &str) -> Result<Region> {
+        availability_zone
+            .parse::<AvailabilityZone>()
+            .map(|zone| zone.region())
+    }
+}
+
+impl FromStr for Region {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        REGIONS
+            .iter()
+            .find(|(name, _)| *name == s)
+            .map(|&(name, partition)| Region {
+                name: Cow::Borrowed(name),
+                partition,
+            })
+            .ok_or_else(|| Error::UnknownRegion(s.to_string()))
+    }
+}
+
+impl AsRef<str> for Region {
+    fn as_ref(&self) -> &str {
+        &self.name
+    }
+}
+
+impl PartialEq<str> for Region {
+    fn eq(&self, other: &str) -> bool {
+        self.name == other
+    }
+}
+
+impl PartialEq<&str> for Region {
+    fn eq(&self, other: &&str) -> bool {
+        self.name == *other
+    }
+}
+
+impl std::fmt::Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for Region {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.name)
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'de> Deserialize<'de> for Region {
+    // Snapshots can hold regions this crate doesn't list, taken from IMDS.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        if !looks_like_region(&s) {
+            return Err(serde::de::Error::custom(Error::UnknownRegion(s)));
+        }
+        Ok(Region::reported(&s, None))
+    }
+}
+
+impl InstanceMetadataClient {
+    /// Get the partition the instance runs in, as reported by IMDS.
+    pub fn get_partition(&self) -> Result<Partition> {
+        self.get_path(SERVICES_PARTITION)?.parse()
+    }
+
+    /// Get the domain for AWS service endpoints in the instance's region,
+    /// e.g. `amazonaws.com` or `amazonaws.com.cn`.
+    pub fn get_domain(&self) -> Result<String> {
+        self.get_path(SERVICES_DOMAIN)
+    }
+}
@@ -0,0 +1,148 @@
+//! Deciding which failed requests to send again, and how long to wait
+//! first.
+
+use std::collections::hash_map::RandomState;
+use std::fmt;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::Outcome;
+
+const DEFAULT_BASE_DELAY_MS: u64 = 100;
+const DEFAULT_MAX_DELAY_MS: u64 = 2000;
+
+/// `RetryPolicy` decides whether a request is sent again after it fails,
+/// and how long the client waits before resending it. Waits that would
+/// pass the client's deadline aren't made; the last response or error is
+/// returned instead.
+///
+/// # Examples:
+/// Also retry `403`s, which some IMDS proxies send while they start up:
+/// ```
+/// # #[cfg(feature = "mock")] {
+/// use std::time::Duration;
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// use ec2_instance_metadata::{ExponentialBackoff, Outcome, RetryPolicy};
+///
+/// struct RetryForbidden(ExponentialBackoff);
+///
+/// impl RetryPolicy for RetryForbidden {
+///     fn should_retry(&self, attempt: u32, outcome: Outcome) -> bool {
+///         match outcome {
+///             Outcome::Status(403) => attempt < 4,
+///             outcome => self.0.should_retry(attempt, outcome),
+///         }
+///     }
+///
+///     fn delay(&self, retry: u32) -> Duration {
+///         self.0.delay(retry)
+///     }
+/// }
+///
+/// let server = MockImdsServer::start().unwrap();
+/// server.set_status("meta-data/instance-id", 403);
+/// let backoff = ExponentialBackoff::new().base_delay(Duration::from_millis(1));
+/// let client = server
+///     .client_builder()
+///     .retry_policy(RetryForbidden(backoff))
+///     .build();
+/// assert!(client.get_path("meta-data/instance-id").is_err());
+/// // One token request, then four tries.
+/// assert_eq!(server.request_count(), 5);
+/// # }
+/// ```
+pub trait RetryPolicy: Send + Sync {
+    /// Whether to send the request again after try number `attempt`,
+    /// starting from 1, ended with `outcome`.
+    fn should_retry(&self, attempt: u32, outcome: Outcome) -> bool;
+
+    /// How long to wait before retry number `retry`, starting from 1.
+    fn delay(&self, retry: u32) -> Duration;
+}
+
+/// `ExponentialBackoff` is the default `RetryPolicy`. It retries throttled
+/// (429) and server error (5xx) responses, waiting a random time of up to
+/// `base_delay`, doubled after each retry and capped at `max_delay`.
+/// Spreading the waits out keeps many clients on one host from retrying
+/// in lockstep.
+///
+/// Requests which fail without a response, e.g. timeouts, aren't retried;
+/// IMDS is local, so they rarely succeed the second time.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ExponentialBackoff {
+    max_attempts: u32,
+    base_delay: Duration,
+    max_delay: Duration,
+}
+
+impl ExponentialBackoff {
+    /// 3 attempts in all, from 100ms, capped at 2s.
+    pub fn new() -> Self {
+        Self {
+            max_attempts: crate::DEFAULT_MAX_ATTEMPTS,
+            base_delay: Duration::from_millis(DEFAULT_BASE_DELAY_MS),
+            max_delay: Duration::from_millis(DEFAULT_MAX_DELAY_MS),
+        }
+    }
+
+    /// Try each request up to `attempts` times in all.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = attempts.max(1);
+        self
+    }
+
+    /// The longest wait before the first retry.
+    pub fn base_delay(mut self, delay: Duration) -> Self {
+        self.base_delay = delay;
+        self
+    }
+
+    /// The longest wait before any retry.
+    pub fn max_delay(mut self, delay: Duration) -> Self {
+        self.max_delay = delay;
+        self
+    }
+}
+
+impl Default for ExponentialBackoff {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RetryPolicy for ExponentialBackoff {
+    fn should_retry(&self, attempt: u32, outcome: Outcome) -> bool {
+        let retryable = match outcome {
+            Outcome::Status(status) => status == 429 || (500..600).contains(&status),
+            Outcome::Timeout | Outcome::Error => false,
+        };
+        retryable && attempt < self.max_attempts
+    }
+
+    fn delay(&self, retry: u32) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(1 << retry.saturating_sub(1).min(16))
+            .min(self.max_delay);
+        backoff.mul_f64(jitter())
+    }
+}
+
+// A number in [0, 1). Good enough to spread retries out without pulling
+// in a random number crate; each `RandomState` is seeded differently.
+fn jitter() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+// Policies are usually user types, which needn't be `Debug`, but the
+// builder and client are.
+#[derive(Clone)]
+pub(crate) struct Retry(pub(crate) Arc<dyn RetryPolicy>);
+
+impl fmt::Debug for Retry {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("RetryPolicy")
+    }
+}
@@ -0,0 +1,33 @@
+//! Interop with the AWS SDK's `aws_types::region::Region`, enabled by the
+//! `aws-types` feature, so the detected region can be handed straight to
+//! SDK client builders.
+
+use crate::{InstanceMetadata, Region};
+
+impl From<Region> for aws_types::region::Region {
+    fn from(region: Region) -> Self {
+        aws_types::region::Region::new(region.name().to_string())
+    }
+}
+
+impl From<&InstanceMetadata> for aws_types::region::Region {
+    fn from(metadata: &InstanceMetadata) -> Self {
+        metadata.region.clone().into()
+    }
+}
+
+impl InstanceMetadata {
+    /// The instance's region as the AWS SDK's own `Region` type. This is
+    /// an accessor rather than a replacement for the `region` field, which
+    /// also carries the partition.
+    ///
+    /// # Examples:
+    /// ```no_run
+    /// let metadata = ec2_instance_metadata::InstanceMetadataClient::new().get().unwrap();
+    /// let region: aws_types::region::Region = metadata.sdk_region();
+    /// // e.g. aws_config::defaults(BehaviorVersion::latest()).region(region)
+    /// ```
+    pub fn sdk_region(&self) -> aws_types::region::Region {
+        self.into()
+    }
+}
@@ -0,0 +1,210 @@
+use std::fmt;
+use std::sync::mpsc::RecvTimeoutError;
+use std::time::{Duration, Instant};
+
+use crate::{
+    InstanceAction, InstanceMetadataClient, Result, SpotInstanceAction, TargetLifecycleState,
+    Watcher,
+};
+
+// AWS recommends checking for interruption notices every 5 seconds.
+const DEFAULT_SHUTDOWN_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Why a `ShutdownSignal` fired.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShutdownReason {
+    /// AWS will stop, hibernate, or terminate the Spot instance at the
+    /// notice's `time`
+    SpotInterruption(SpotInstanceAction),
+    /// IMDS reported that the instance is about to be shut down
+    InstanceAction(InstanceAction),
+    /// Auto Scaling is taking the instance out of service for good
+    AutoScaling(TargetLifecycleState),
+    /// The Spot instance is at elevated risk of interruption. The notice
+    /// itself usually follows, but may never come.
+    RebalanceRecommendation {
+        /// When the recommendation was made, in UTC
+        notice_time: String,
+    },
+}
+
+impl fmt::Display for ShutdownReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShutdownReason::SpotInterruption(notice) => {
+                write!(f, "spot interruption: {} at {}", notice.action, notice.time)
+            }
+            ShutdownReason::InstanceAction(action) => write!(f, "instance action: {}", action),
+            ShutdownReason::AutoScaling(state) => write!(f, "auto scaling: {}", state),
+            ShutdownReason::RebalanceRecommendation { notice_time } => {
+                write!(f, "rebalance recommendation at {}", notice_time)
+            }
+        }
+    }
+}
+
+/// `ShutdownSignal` polls IMDS in a background thread for any sign that
+/// the instance is going away: a Spot interruption notice, a requested
+/// instance action, an Auto Scaling termination, or a rebalance
+/// recommendation. Once it fires it keeps the first reason it saw and
+/// stops polling. Polling stops when the signal is dropped.
+///
+/// Polls run in the background from the start, so creating a signal
+/// doesn't wait for IMDS. Failed polls, including the first, are retried
+/// on the next interval.
+///
+/// # Examples:
+/// ```
+/// # #[cfg(feature = "mock")] {
+/// use std::time::Duration;
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// use ec2_instance_metadata::{InstanceAction, ShutdownReason};
+/// let server = MockImdsServer::start().unwrap();
+/// let signal = server
+///     .client()
+///     .shutdown_signal_every(Duration::from_millis(10))
+///     .unwrap();
+/// assert_eq!(signal.reason(), None);
+///
+/// server.set("meta-data/instance-action", "shutdown");
+/// assert_eq!(
+///     signal.wait_timeout(Duration::from_secs(5)),
+///     Some(ShutdownReason::InstanceAction(InstanceAction::Shutdown))
+/// );
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct ShutdownSignal {
+    watcher: Watcher<Option<ShutdownReason>>,
+    interval: Duration,
+}
+
+impl ShutdownSignal {
+    /// The reason the signal fired, or `None` if it hasn't yet.
+    pub fn reason(&self) -> Option<ShutdownReason> {
+        (*self.watcher.current()).clone()
+    }
+
+    /// True once the signal has fired.
+    pub fn is_fired(&self) -> bool {
+        self.watcher.current().is_some()
+    }
+
+    /// Block until the signal fires. Returns `None` if the client's
+    /// `CancellationToken` is cancelled first, since the signal can no
+    /// longer fire.
+    pub fn wait(&self) -> Option<ShutdownReason> {
+        loop {
+            if let Some(reason) = self.reason() {
+                return Some(reason);
+            }
+            // Events can be dropped if nobody reads them, so fall back to
+            // checking the snapshot every interval.
+            let event = self.watcher.events().recv_timeout(self.interval);
+            if let Err(RecvTimeoutError::Disconnected) = event {
+                return self.reason();
+            }
+        }
+    }
+
+    /// Block until the signal fires or `timeout` elapses, or the client is
+    /// cancelled.
+    pub fn wait_timeout(&self, timeout: Duration) -> Option<ShutdownReason> {
+        let deadline = Instant::now() + timeout;
+        loop {
+            if let Some(reason) = self.reason() {
+                return Some(reason);
+            }
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining.is_zero() {
+                return None;
+            }
+            let event = self
+                .watcher
+                .events()
+                .recv_timeout(remaining.min(self.interval));
+            if let Err(RecvTimeoutError::Disconnected) = event {
+                return self.reason();
+            }
+        }
+    }
+}
+
+impl InstanceMetadataClient {
+    /// Check once for a reason to shut down, most urgent first.
+    pub fn get_shutdown_reason(&self) -> Result<Option<ShutdownReason>> {
+        let spot = self.get_spot_info()?;
+        if let Some(notice) = spot.instance_action {
+            return Ok(Some(ShutdownReason::SpotInterruption(notice)));
+        }
+
+        let action = self.get_instance_action()?;
+        // Bundling an instance store-backed instance isn't a shutdown.
+        if action.is_shutdown() {
+            return Ok(Some(ShutdownReason::InstanceAction(action)));
+        }
+
+        if let Some(state) = self.get_target_lifecycle_state()? {
+            if state.is_terminating() {
+                return Ok(Some(ShutdownReason::AutoScaling(state)));
+            }
+        }
+
+        Ok(spot
+            .rebalance_recommended_at
+            .map(|notice_time| ShutdownReason::RebalanceRecommendation { notice_time }))
+    }
+
+    /// Start a `ShutdownSignal` which polls every 5 seconds, as AWS
+    /// recommends for Spot interruption notices.
+    ///
+    /// # Examples:
+    /// ```no_run
+    /// use ec2_instance_metadata::InstanceMetadataClient;
+    /// let signal = InstanceMetadataClient::new().shutdown_signal().unwrap();
+    /// if let Some(reason) = signal.wait() {
+    ///     println!("draining: {}", reason);
+    /// }
+    /// ```
+    pub fn shutdown_signal(self) -> Result<ShutdownSignal> {
+        self.shutdown_signal_every(DEFAULT_SHUTDOWN_POLL_INTERVAL)
+    }
+
+    /// Start a `ShutdownSignal` which polls every `interval`. Doesn't
+    /// fail; the first poll happens in the background, and is retried if
+    /// IMDS isn't answering yet.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use std::time::Duration;
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::{InstanceAction, ShutdownReason};
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set_status("meta-data/spot/instance-action", 503);
+    /// let client = server.client_builder().max_attempts(1).build();
+    /// let signal = client.shutdown_signal_every(Duration::from_millis(10)).unwrap();
+    ///
+    /// // Bundling isn't a reason to shut down.
+    /// server.remove("meta-data/spot/instance-action");
+    /// server.set("meta-data/instance-action", "bundle-pending");
+    /// assert_eq!(signal.wait_timeout(Duration::from_millis(200)), None);
+    ///
+    /// server.set("meta-data/instance-action", "shutdown");
+    /// assert_eq!(
+    ///     signal.wait_timeout(Duration::from_secs(5)),
+    ///     Some(ShutdownReason::InstanceAction(InstanceAction::Shutdown))
+    /// );
+    /// # }
+    /// ```
+    pub fn shutdown_signal_every(self, interval: Duration) -> Result<ShutdownSignal> {
+        let mut fired = None;
+        let watcher = Watcher::spawn_from(None, interval, move || {
+            if fired.is_none() {
+                fired = self.get_shutdown_reason()?;
+            }
+            Ok(fired.clone())
+        });
+        Ok(ShutdownSignal { watcher, interval })
+    }
+}
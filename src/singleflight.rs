@@ -0,0 +1,98 @@
+//! Coalescing of concurrent identical requests: while one caller is
+//! fetching a key, others asking for the same key wait and share its
+//! result instead of sending their own requests.
+
+use std::collections::HashMap;
+use std::sync::{Arc, Condvar, Mutex, MutexGuard, PoisonError};
+
+use crate::{Error, Result};
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[derive(Debug)]
+struct Call<T> {
+    result: Mutex<Option<Result<T>>>,
+    done: Condvar,
+}
+
+#[derive(Debug)]
+pub(crate) struct SingleFlight<T> {
+    calls: Mutex<HashMap<String, Arc<Call<T>>>>,
+}
+
+// Publishes the leader's result and wakes the waiters, even if the
+// leader's fetch panicked.
+struct Completion<'a, T> {
+    flight: &'a SingleFlight<T>,
+    key: &'a str,
+    call: Arc<Call<T>>,
+    result: Option<Result<T>>,
+}
+
+impl<T> Drop for Completion<'_, T> {
+    fn drop(&mut self) {
+        let result = self.result.take().unwrap_or_else(|| {
+            Err(Error::HttpRequest(
+                "coalesced request panicked before completing".into(),
+            ))
+        });
+        lock(&self.flight.calls).remove(self.key);
+        *lock(&self.call.result) = Some(result);
+        self.call.done.notify_all();
+    }
+}
+
+impl<T: Clone> SingleFlight<T> {
+    pub(crate) fn new() -> Self {
+        Self {
+            calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Run `fetch` for `key`, unless another thread already is, in which
+    /// case wait for and return its result.
+    pub(crate) fn run<F>(&self, key: &str, fetch: F) -> Result<T>
+    where
+        F: FnOnce() -> Result<T>,
+    {
+        let (call, leader) = {
+            let mut calls = lock(&self.calls);
+            match calls.get(key) {
+                Some(call) => (Arc::clone(call), false),
+                None => {
+                    let call = Arc::new(Call {
+                        result: Mutex::new(None),
+                        done: Condvar::new(),
+                    });
+                    calls.insert(key.to_string(), Arc::clone(&call));
+                    (call, true)
+                }
+            }
+        };
+
+        if leader {
+            let mut completion = Completion {
+                flight: self,
+                key,
+                call: Arc::clone(&call),
+                result: None,
+            };
+            let result = fetch();
+            completion.result = Some(result.clone());
+            return result;
+        }
+
+        let mut result = lock(&call.result);
+        while result.is_none() {
+            result = call
+                .done
+                .wait(result)
+                .unwrap_or_else(PoisonError::into_inner);
+        }
+        result
+            .clone()
+            .expect("result is set before waiters are woken")
+    }
+}
@@ -0,0 +1,171 @@
+//! Picking where metadata comes from when the process may be running on an
+//! instance, in an ECS or Fargate task, or in an EKS pod.
+
+use serde::Serialize;
+
+use crate::ecs::{EcsMetadataClient, TaskMetadata, ECS_METADATA_ENV_VAR};
+use crate::{AvailabilityZone, Error, InstanceMetadata, InstanceMetadataClient, Region, Result};
+
+/// ECS sets this to `AWS_ECS_FARGATE` or `AWS_ECS_EC2` in task containers.
+const EXECUTION_ENV_VAR: &str = "AWS_EXECUTION_ENV";
+const FARGATE_EXECUTION_ENV: &str = "AWS_ECS_FARGATE";
+/// Kubernetes sets this in every pod.
+const KUBERNETES_ENV_VAR: &str = "KUBERNETES_SERVICE_HOST";
+
+/// Where a `MetadataSource` gets its metadata from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SourceKind {
+    /// The ECS task metadata endpoint, in a task on Fargate
+    Fargate,
+    /// The ECS task metadata endpoint, in a task on an EC2 container
+    /// instance
+    Ecs,
+    /// IMDS, from a Kubernetes pod on an EC2 node
+    Eks,
+    /// IMDS, on an EC2 instance
+    Ec2,
+}
+
+impl std::fmt::Display for SourceKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let name = match self {
+            SourceKind::Fargate => "fargate",
+            SourceKind::Ecs => "ecs",
+            SourceKind::Eks => "eks",
+            SourceKind::Ec2 => "ec2",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// `DetectedMetadata` is what a `MetadataSource` fetched, tagged with where
+/// it came from.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct DetectedMetadata {
+    pub source: SourceKind,
+
+    /// Available from every source, except from ECS tasks on container
+    /// agents too old to report it
+    pub region: Option<Region>,
+
+    /// Available from every source, except from ECS tasks on container
+    /// agents too old to report it
+    pub availability_zone: Option<AvailabilityZone>,
+
+    /// The instance's metadata, from EKS and EC2
+    pub instance: Option<InstanceMetadata>,
+
+    /// The task's metadata, from ECS and Fargate
+    pub task: Option<TaskMetadata>,
+}
+
+/// `MetadataSource` is a metadata client for whichever environment the
+/// process turns out to be running in.
+///
+/// # Examples:
+/// ```no_run
+/// use ec2_instance_metadata::MetadataSource;
+/// let source = MetadataSource::autodetect().expect("not running on AWS");
+/// let metadata = source.get().unwrap();
+/// println!("{} in {:?}", metadata.source, metadata.availability_zone);
+/// ```
+#[derive(Debug)]
+pub enum MetadataSource {
+    Fargate(EcsMetadataClient),
+    Ecs(EcsMetadataClient),
+    Eks(InstanceMetadataClient),
+    Ec2(InstanceMetadataClient),
+}
+
+impl MetadataSource {
+    /// Pick a source, checking in order for the ECS task metadata endpoint,
+    /// then for IMDS from a Kubernetes pod, then for IMDS on the instance.
+    /// Fails with `Error::NotFound` if none of them is available.
+    pub fn autodetect() -> Result<Self> {
+        Self::autodetect_with(InstanceMetadataClient::new())
+    }
+
+    /// Same as `autodetect()`, using `client` if the source turns out to
+    /// be IMDS.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::MetadataSource;
+    /// # if std::env::var("ECS_CONTAINER_METADATA_URI_V4").is_err() {
+    /// let server = MockImdsServer::start().unwrap();
+    /// let source = MetadataSource::autodetect_with(server.client()).unwrap();
+    /// let metadata = source.get().unwrap();
+    /// assert_eq!(metadata.availability_zone.unwrap(), "us-east-1a");
+    /// assert!(metadata.instance.is_some());
+    /// # }
+    /// # }
+    /// ```
+    pub fn autodetect_with(client: InstanceMetadataClient) -> Result<Self> {
+        if let Ok(ecs) = EcsMetadataClient::from_env() {
+            let fargate = std::env::var(EXECUTION_ENV_VAR)
+                .is_ok_and(|env| env.trim() == FARGATE_EXECUTION_ENV);
+            return Ok(if fargate {
+                MetadataSource::Fargate(ecs)
+            } else {
+                MetadataSource::Ecs(ecs)
+            });
+        }
+
+        if !client.ping().is_available() {
+            return Err(Error::NotFound(format!(
+                "neither {} nor IMDS is available",
+                ECS_METADATA_ENV_VAR
+            )));
+        }
+        let in_pod = std::env::var_os(KUBERNETES_ENV_VAR).is_some();
+        Ok(if in_pod {
+            MetadataSource::Eks(client)
+        } else {
+            MetadataSource::Ec2(client)
+        })
+    }
+
+    pub fn kind(&self) -> SourceKind {
+        match self {
+            MetadataSource::Fargate(_) => SourceKind::Fargate,
+            MetadataSource::Ecs(_) => SourceKind::Ecs,
+            MetadataSource::Eks(_) => SourceKind::Eks,
+            MetadataSource::Ec2(_) => SourceKind::Ec2,
+        }
+    }
+
+    /// Fetch the task metadata from ECS and Fargate, or the instance
+    /// metadata from IMDS.
+    pub fn get(&self) -> Result<DetectedMetadata> {
+        match self {
+            MetadataSource::Fargate(ecs) | MetadataSource::Ecs(ecs) => {
+                let task = ecs.get_task()?;
+                let availability_zone: Option<AvailabilityZone> = match &task.availability_zone {
+                    Some(zone) => Some(zone.parse()?),
+                    None => None,
+                };
+                let region = availability_zone.as_ref().map(AvailabilityZone::region);
+                Ok(DetectedMetadata {
+                    source: self.kind(),
+                    region,
+                    availability_zone,
+                    instance: None,
+                    task: Some(task),
+                })
+            }
+            MetadataSource::Eks(client) | MetadataSource::Ec2(client) => {
+                let instance = client.get()?;
+                Ok(DetectedMetadata {
+                    source: self.kind(),
+                    region: Some(instance.region.clone()),
+                    availability_zone: Some(instance.availability_zone.clone()),
+                    instance: Some(instance),
+                    task: None,
+                })
+            }
+        }
+    }
+}
@@ -0,0 +1,155 @@
+use std::time::Duration;
+
+#[cfg(feature = "json")]
+use serde::Serialize;
+
+use crate::flat_json::required_string_field;
+use crate::lifecycle::INSTANCE_LIFE_CYCLE;
+use crate::{missing_as_none, Error, InstanceLifeCycle, InstanceMetadataClient, Result, Watcher};
+
+const SPOT_INSTANCE_ACTION: &str = "meta-data/spot/instance-action";
+const SPOT_TERMINATION_TIME: &str = "meta-data/spot/termination-time";
+pub(crate) const REBALANCE_RECOMMENDATION: &str = "meta-data/events/recommendations/rebalance";
+
+/// `SpotInstanceAction` is the interruption notice AWS publishes roughly
+/// two minutes before it stops, hibernates, or terminates a Spot instance.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct SpotInstanceAction {
+    /// `stop`, `hibernate`, or `terminate`
+    pub action: String,
+
+    /// When the action will happen, in UTC (e.g. `2017-09-18T08:22:00Z`)
+    pub time: String,
+}
+
+/// `SpotInfo` gathers everything IMDS says about the instance's Spot
+/// status.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "json", derive(Serialize))]
+pub struct SpotInfo {
+    /// The purchasing option, if IMDS serves it
+    pub life_cycle: Option<InstanceLifeCycle>,
+
+    /// The pending interruption notice, if there is one
+    pub instance_action: Option<SpotInstanceAction>,
+
+    /// When the instance will be terminated, from the older
+    /// `spot/termination-time` category. Only set alongside a `terminate`
+    /// notice.
+    pub termination_time: Option<String>,
+
+    /// When EC2 signalled that the instance is at elevated risk of
+    /// interruption, in UTC. Usually arrives ahead of the notice itself.
+    pub rebalance_recommended_at: Option<String>,
+}
+
+impl SpotInfo {
+    /// True if the instance is a Spot instance. Interruption notices and
+    /// rebalance recommendations count too, in case the life cycle wasn't
+    /// served.
+    pub fn is_spot(&self) -> bool {
+        self.life_cycle == Some(InstanceLifeCycle::Spot)
+            || self.instance_action.is_some()
+            || self.rebalance_recommended_at.is_some()
+    }
+
+    /// True if an interruption notice has been issued.
+    pub fn is_interrupting(&self) -> bool {
+        self.instance_action.is_some()
+    }
+}
+
+fn parse_instance_action(body: &str) -> Result<SpotInstanceAction> {
+    Ok(SpotInstanceAction {
+        action: required_string_field(body, "action")?,
+        time: required_string_field(body, "time")?,
+    })
+}
+
+impl InstanceMetadataClient {
+    /// Get the pending Spot interruption notice, if there is one. On-demand
+    /// instances never have one.
+    pub fn get_spot_instance_action(&self) -> Result<Option<SpotInstanceAction>> {
+        match self.fetch_path(SPOT_INSTANCE_ACTION) {
+            Ok(body) => parse_instance_action(&body).map(Some),
+            Err(Error::NotFound(_)) => Ok(None),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Get the instance's life cycle, interruption notice, and rebalance
+    /// recommendation together.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set("meta-data/instance-life-cycle", "spot");
+    /// server.set(
+    ///     "meta-data/events/recommendations/rebalance",
+    ///     r#"{"noticeTime": "2024-01-01T08:20:00Z"}"#,
+    /// );
+    ///
+    /// let spot = server.client().get_spot_info().unwrap();
+    /// assert!(spot.is_spot());
+    /// assert!(!spot.is_interrupting());
+    /// assert_eq!(spot.rebalance_recommended_at.as_deref(), Some("2024-01-01T08:20:00Z"));
+    /// # }
+    /// ```
+    pub fn get_spot_info(&self) -> Result<SpotInfo> {
+        let values = self.get_many(&[
+            INSTANCE_LIFE_CYCLE,
+            SPOT_INSTANCE_ACTION,
+            SPOT_TERMINATION_TIME,
+            REBALANCE_RECOMMENDATION,
+        ])?;
+        let optional = |path: &str| missing_as_none(values[path].clone());
+
+        let instance_action = match optional(SPOT_INSTANCE_ACTION)? {
+            Some(body) => Some(parse_instance_action(&body)?),
+            None => None,
+        };
+        let rebalance_recommended_at = match optional(REBALANCE_RECOMMENDATION)? {
+            Some(body) => Some(required_string_field(&body, "noticeTime")?),
+            None => None,
+        };
+
+        Ok(SpotInfo {
+            life_cycle: optional(INSTANCE_LIFE_CYCLE)?.map(|s| InstanceLifeCycle::from(s.as_str())),
+            instance_action,
+            termination_time: optional(SPOT_TERMINATION_TIME)?.map(|s| s.trim().to_string()),
+            rebalance_recommended_at,
+        })
+    }
+
+    /// Start a `Watcher` which polls for a Spot interruption notice every
+    /// `interval`. AWS recommends checking every 5 seconds.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use std::time::Duration;
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// use ec2_instance_metadata::WatchEvent;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let watcher = server.client().watch_spot(Duration::from_millis(50)).unwrap();
+    /// assert_eq!(*watcher.current(), None);
+    ///
+    /// server.set(
+    ///     "meta-data/spot/instance-action",
+    ///     r#"{"action": "terminate", "time": "2017-09-18T08:22:00Z"}"#,
+    /// );
+    /// match watcher.events().recv_timeout(Duration::from_secs(5)).unwrap() {
+    ///     WatchEvent::Changed { current, .. } => {
+    ///         assert_eq!(current.as_ref().as_ref().unwrap().action, "terminate");
+    ///     }
+    ///     event => panic!("unexpected {:?}", event),
+    /// }
+    /// # }
+    /// ```
+    pub fn watch_spot(self, interval: Duration) -> Result<Watcher<Option<SpotInstanceAction>>> {
+        Watcher::spawn(interval, move || self.get_spot_instance_action())
+    }
+}
@@ -0,0 +1,142 @@
+use std::convert::TryFrom;
+use std::fmt;
+use std::io::{self, Read};
+
+#[cfg(any(feature = "json", feature = "gcp"))]
+use crate::transport::{ImdsTransport, Request, Response};
+use crate::{check_status, Error, InstanceMetadataClient, Result};
+
+const USER_DATA: &str = "user-data";
+
+/// `MetadataReader` reads a metadata value as it arrives, rather than
+/// buffering the whole response first. Reads fail with
+/// `io::ErrorKind::InvalidData` once the body grows past the client's
+/// `max_body_size`.
+pub struct MetadataReader {
+    body: Box<dyn Read + Send>,
+    limit: u64,
+    read: u64,
+}
+
+impl MetadataReader {
+    /// Wrap `body`, failing reads once more than `limit` bytes are read.
+    fn new(body: Box<dyn Read + Send>, limit: u64) -> Self {
+        Self {
+            body,
+            limit,
+            read: 0,
+        }
+    }
+}
+
+impl fmt::Debug for MetadataReader {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("MetadataReader")
+            .field("limit", &self.limit)
+            .field("read", &self.read)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Read for MetadataReader {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let limit = self.limit;
+
+        // Ask for one byte past the limit, so a body of exactly `limit`
+        // bytes reads cleanly but anything longer is caught.
+        let allowed = limit.saturating_sub(self.read).saturating_add(1);
+        let len = buf
+            .len()
+            .min(usize::try_from(allowed).unwrap_or(usize::MAX));
+        let n = self.body.read(&mut buf[..len])?;
+
+        self.read += n as u64;
+        if self.read > limit {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("metadata response is larger than {} bytes", limit),
+            ));
+        }
+        Ok(n)
+    }
+}
+
+// Reads a whole body, failing with `Error::MalformedResponse` as soon as
+// it grows past `limit`, so an oversized body is never held in memory.
+pub(crate) fn read_body(body: Box<dyn Read + Send>, limit: u64, path: &str) -> Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    match MetadataReader::new(body, limit).read_to_end(&mut bytes) {
+        Ok(_) => Ok(bytes),
+        Err(e) if e.kind() == io::ErrorKind::InvalidData => {
+            Err(Error::MalformedResponse(format!("{}: {}", path, e)))
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+// Sends `request` through `transport` and reads the body into memory, up
+// to `limit`, for the clients of metadata services other than IMDS.
+#[cfg(any(feature = "json", feature = "gcp"))]
+pub(crate) fn send_bounded(
+    transport: &dyn ImdsTransport,
+    request: &Request,
+    limit: u64,
+    path: &str,
+) -> Result<Response> {
+    let resp = transport.send_streaming(request)?;
+    Ok(Response {
+        status: resp.status,
+        headers: resp.headers,
+        body: read_body(resp.body, limit, path)?,
+    })
+}
+
+// Reads a whole text value, failing with `Error::MalformedResponse` if
+// it's larger than `limit`, isn't UTF-8 or contains a NUL. Text values,
+// unlike binary user data, never legitimately do.
+pub(crate) fn read_value(body: Box<dyn Read + Send>, limit: u64, path: &str) -> Result<String> {
+    let malformed = |reason: String| Error::MalformedResponse(format!("{}: {}", path, reason));
+
+    let bytes = read_body(body, limit, path)?;
+    if let Some(offset) = bytes.iter().position(|&b| b == 0) {
+        return Err(malformed(format!("NUL byte at offset {}", offset)));
+    }
+    String::from_utf8(bytes).map_err(|e| malformed(e.to_string()))
+}
+
+impl InstanceMetadataClient {
+    /// Get a reader for the value at an arbitrary metadata path, relative
+    /// to `/latest/`, without buffering it in memory. Never cached.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use std::io::Read;
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set("user-data", "#!/bin/sh\necho hello\n");
+    ///
+    /// let client = server.client_builder().max_body_size(16).build();
+    /// let mut user_data = String::new();
+    /// let result = client.get_user_data_reader().unwrap().read_to_string(&mut user_data);
+    /// assert!(result.is_err());
+    /// # }
+    /// ```
+    pub fn get_path_reader(&self, path: &str) -> Result<MetadataReader> {
+        let token = self.get_token()?;
+        let url = format!("{}{}", self.inner.base_url, path);
+
+        let resp = self.get_with_token(&url, path, &token, None, |request| {
+            self.inner.transport.send_streaming(request)
+        })?;
+        check_status(resp.status, path)?;
+
+        Ok(MetadataReader::new(resp.body, self.inner.max_body_size))
+    }
+
+    /// Get a reader for the instance's user data. Fails with
+    /// `Error::NotFound` if the instance was launched without any.
+    pub fn get_user_data_reader(&self) -> Result<MetadataReader> {
+        self.get_path_reader(USER_DATA)
+    }
+}
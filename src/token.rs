@@ -0,0 +1,44 @@
+use std::sync::{Mutex, MutexGuard, PoisonError};
+use std::time::{Duration, Instant};
+
+/// How long requested tokens stay valid, in seconds. This is the maximum
+/// IMDS allows.
+pub(crate) const TOKEN_TTL_SECS: u64 = 21600;
+
+// Tokens are re-fetched this long before they expire, so a request never
+// goes out with a token that lapses in flight.
+const EXPIRY_MARGIN: Duration = Duration::from_secs(60);
+
+/// Holds the current IMDSv2 session token, so every request doesn't need
+/// a token PUT of its own.
+#[derive(Debug, Default)]
+pub(crate) struct TokenCache {
+    current: Mutex<Option<(String, Instant)>>,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl TokenCache {
+    pub(crate) fn get(&self) -> Option<String> {
+        let lifetime = Duration::from_secs(TOKEN_TTL_SECS) - EXPIRY_MARGIN;
+        lock(&self.current)
+            .as_ref()
+            .filter(|(_, fetched_at)| fetched_at.elapsed() < lifetime)
+            .map(|(token, _)| token.clone())
+    }
+
+    pub(crate) fn put(&self, token: String) {
+        *lock(&self.current) = Some((token, Instant::now()));
+    }
+
+    /// Forget `token` after IMDS rejected it. A token another thread has
+    /// already replaced it with is kept.
+    pub(crate) fn invalidate(&self, token: &str) {
+        let mut current = lock(&self.current);
+        if current.as_ref().is_some_and(|(t, _)| t == token) {
+            *current = None;
+        }
+    }
+}
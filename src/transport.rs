@@ -0,0 +1,359 @@
+//! The HTTP layer underneath `InstanceMetadataClient`.
+//!
+//! The client only needs to send bodiless GET and PUT requests and read
+//! back the status, headers, and body, which is all `ImdsTransport` asks
+//! of an implementation. The `ureq` backend is enabled by default;
+//! `reqwest` and `hyper` backends are available behind features of the
+//! same names so applications already using them don't pull in a second
+//! HTTP stack.
+
+use std::fmt::{self, Debug};
+use std::io::{Cursor, Read};
+use std::net::IpAddr;
+use std::time::Duration;
+
+use crate::{Error, Result};
+
+#[cfg(feature = "hyper")]
+mod hyper;
+#[cfg(feature = "reqwest")]
+mod reqwest;
+#[cfg(feature = "ureq")]
+mod ureq;
+
+#[cfg(feature = "hyper")]
+pub use self::hyper::HyperTransport;
+#[cfg(feature = "reqwest")]
+pub use self::reqwest::ReqwestTransport;
+#[cfg(feature = "ureq")]
+pub use self::ureq::UreqTransport;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Put,
+}
+
+impl Method {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Put => "PUT",
+        }
+    }
+}
+
+/// A request to IMDS. IMDS requests never carry a body.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: Method,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    /// If set, the request must complete within this long, even if the
+    /// transport's own timeouts would allow longer. Set when the request
+    /// is part of an operation with a deadline.
+    pub timeout: Option<Duration>,
+}
+
+impl Request {
+    pub fn new(method: Method, url: impl Into<String>) -> Self {
+        Self {
+            method,
+            url: url.into(),
+            headers: Vec::new(),
+            timeout: None,
+        }
+    }
+
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+}
+
+/// A response from IMDS. Non-2xx statuses are returned as responses, not
+/// errors; it's up to the client to interpret them.
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Look up a header, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    pub fn text(self) -> Result<String> {
+        String::from_utf8(self.body).map_err(|e| Error::IoError(format!("{:?}", e)))
+    }
+}
+
+/// A response whose body is read incrementally instead of being buffered
+/// up front. See `ImdsTransport::send_streaming`.
+pub struct StreamingResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Box<dyn Read + Send>,
+}
+
+impl StreamingResponse {
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+impl Debug for StreamingResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("StreamingResponse")
+            .field("status", &self.status)
+            .field("headers", &self.headers)
+            .finish_non_exhaustive()
+    }
+}
+
+impl From<Response> for StreamingResponse {
+    fn from(resp: Response) -> Self {
+        Self {
+            status: resp.status,
+            headers: resp.headers,
+            body: Box::new(Cursor::new(resp.body)),
+        }
+    }
+}
+
+// Lets the client retry and instrument both kinds of response the same way.
+pub(crate) trait HasStatus {
+    fn status(&self) -> u16;
+    fn headers(&self) -> &[(String, String)];
+}
+
+impl HasStatus for Response {
+    fn status(&self) -> u16 {
+        self.status
+    }
+
+    fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+}
+
+impl HasStatus for StreamingResponse {
+    fn status(&self) -> u16 {
+        self.status
+    }
+
+    fn headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+}
+
+/// `ImdsTransport` sends a single HTTP request and returns the response.
+/// Errors are reserved for transport failures such as timeouts or refused
+/// connections. Timeouts should be reported as `Error::Timeout`, which the
+/// client tells apart from other failures.
+pub trait ImdsTransport: Send + Sync + Debug {
+    fn send(&self, request: &Request) -> Result<Response>;
+
+    /// Like `send()`, but returns before the body has been read. The
+    /// client sends every IMDS request this way, so it can stop reading a
+    /// body once it passes `max_body_size`. The default implementation
+    /// buffers the body with `send()` first, so transports which can
+    /// stream should override it.
+    fn send_streaming(&self, request: &Request) -> Result<StreamingResponse> {
+        self.send(request).map(StreamingResponse::from)
+    }
+}
+
+/// `ProxyMode` controls whether IMDS requests go through an HTTP proxy.
+///
+/// Proxies configured through `HTTP_PROXY`/`http_proxy` usually can't
+/// reach the link-local IMDS address, which only exists on the instance
+/// itself, so by default they're ignored for it.
+///
+/// # Examples:
+/// ```
+/// // The hyper transport doesn't support proxies.
+/// # #[cfg(all(feature = "mock", any(feature = "ureq", feature = "reqwest")))] {
+/// use ec2_instance_metadata::mock::MockImdsServer;
+/// use ec2_instance_metadata::{InstanceMetadataClient, ProxyMode};
+/// // Nothing listens on the discard port, so anything proxied fails.
+/// for var in ["HTTP_PROXY", "http_proxy", "ALL_PROXY", "all_proxy"] {
+///     std::env::set_var(var, "http://127.0.0.1:9");
+/// }
+/// for var in ["NO_PROXY", "no_proxy"] {
+///     std::env::remove_var(var);
+/// }
+///
+/// let server = MockImdsServer::start().unwrap();
+/// let get = |proxy: ProxyMode| {
+///     let client = server.client_builder().proxy(proxy).max_attempts(1).build();
+///     client.get_path("meta-data/instance-id")
+/// };
+/// // The mock listens on a loopback address, which isn't proxied by default.
+/// assert!(get(ProxyMode::Auto).is_ok());
+/// assert!(get(ProxyMode::Environment).is_err());
+///
+/// // The mock stands in for the proxy here, and sees the request.
+/// let proxy = MockImdsServer::start().unwrap();
+/// let client = InstanceMetadataClient::builder()
+///     .endpoint("http://192.0.2.1")
+///     .proxy(ProxyMode::Url(proxy.endpoint()))
+///     .max_attempts(1)
+///     .build();
+/// let _ = client.get_path("meta-data/instance-id");
+/// assert!(proxy.request_count() > 0);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum ProxyMode {
+    /// Don't proxy link-local or loopback endpoints, such as the default
+    /// `169.254.169.254`; use the proxy environment variables for any
+    /// other endpoint.
+    #[default]
+    Auto,
+    /// Never use a proxy.
+    Bypass,
+    /// Use the proxy environment variables, even for link-local endpoints.
+    Environment,
+    /// Send every request through this proxy, e.g. `http://proxy:3128`.
+    Url(String),
+}
+
+impl ProxyMode {
+    // Decide what `Auto` means for a particular endpoint, so transports
+    // only ever see `Bypass`, `Environment`, or `Url`.
+    pub(crate) fn resolve(self, endpoint: &str) -> ProxyMode {
+        match self {
+            ProxyMode::Auto if is_local_endpoint(endpoint) => ProxyMode::Bypass,
+            ProxyMode::Auto => ProxyMode::Environment,
+            mode => mode,
+        }
+    }
+}
+
+// True for link-local and loopback hosts, including the IPv6 IMDS
+// endpoint `fd00:ec2::254`.
+fn is_local_endpoint(endpoint: &str) -> bool {
+    let authority = endpoint
+        .split_once("://")
+        .map_or(endpoint, |(_, rest)| rest);
+    let authority = authority.split('/').next().unwrap_or_default();
+    let host = match authority.strip_prefix('[') {
+        Some(rest) => rest.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+
+    if host.eq_ignore_ascii_case("localhost") {
+        return true;
+    }
+
+    match host.parse::<IpAddr>() {
+        Ok(IpAddr::V4(ip)) => ip.is_link_local() || ip.is_loopback(),
+        Ok(IpAddr::V6(ip)) => {
+            const IMDS_V6: [u16; 8] = [0xfd00, 0x0ec2, 0, 0, 0, 0, 0, 0x0254];
+            ip.is_loopback() || (ip.segments()[0] & 0xffc0) == 0xfe80 || ip.segments() == IMDS_V6
+        }
+        Err(_) => false,
+    }
+}
+
+/// `TransportConfig` holds the settings the built-in transports are
+/// constructed with.
+#[derive(Debug, Clone)]
+pub struct TransportConfig {
+    /// Give up on establishing a connection after this long.
+    pub connect_timeout: Duration,
+    /// Give up waiting for a response after this long, once connected.
+    pub read_timeout: Duration,
+    /// Proxy settings. `ProxyMode::Auto` is treated as
+    /// `ProxyMode::Environment` by transports.
+    pub proxy: ProxyMode,
+    /// Keep up to this many idle connections open for reuse.
+    pub max_idle_connections: usize,
+    /// Close idle connections after this long.
+    pub idle_timeout: Duration,
+    /// Reuse connections between requests. When off, every request opens
+    /// a new connection and the idle settings are ignored.
+    pub keep_alive: bool,
+}
+
+impl TransportConfig {
+    // How many idle connections transports should keep, with keep-alive
+    // taken into account.
+    #[cfg(any(feature = "ureq", feature = "reqwest", feature = "hyper"))]
+    pub(crate) fn idle_connections(&self) -> usize {
+        if self.keep_alive {
+            self.max_idle_connections
+        } else {
+            0
+        }
+    }
+}
+
+impl Default for TransportConfig {
+    fn default() -> Self {
+        Self {
+            connect_timeout: Duration::from_millis(crate::REQUEST_TIMEOUT_MS),
+            read_timeout: Duration::from_millis(crate::REQUEST_TIMEOUT_MS),
+            proxy: ProxyMode::Bypass,
+            max_idle_connections: crate::DEFAULT_MAX_CONCURRENCY,
+            idle_timeout: Duration::from_secs(crate::IDLE_TIMEOUT_SECS),
+            keep_alive: true,
+        }
+    }
+}
+
+/// Stands in for a default transport which couldn't be built, e.g.
+/// because the crate has no HTTP backend or the proxy URL is malformed,
+/// and fails every request with the reason.
+#[cfg(any(feature = "ureq", feature = "reqwest", not(feature = "hyper")))]
+#[derive(Debug)]
+struct FailedTransport(Error);
+
+#[cfg(any(feature = "ureq", feature = "reqwest", not(feature = "hyper")))]
+impl ImdsTransport for FailedTransport {
+    fn send(&self, _request: &Request) -> Result<Response> {
+        Err(self.0.clone())
+    }
+}
+
+// Picks the backend used when the builder isn't given a transport,
+// preferring ureq, then reqwest, then hyper.
+#[allow(unreachable_code)]
+pub(crate) fn default_transport(config: &TransportConfig) -> Box<dyn ImdsTransport> {
+    #[cfg(feature = "ureq")]
+    return match UreqTransport::try_new(config) {
+        Ok(transport) => Box::new(transport),
+        Err(e) => Box::new(FailedTransport(e)),
+    };
+    #[cfg(feature = "reqwest")]
+    return match ReqwestTransport::try_new(config) {
+        Ok(transport) => Box::new(transport),
+        Err(e) => Box::new(FailedTransport(e)),
+    };
+    #[cfg(feature = "hyper")]
+    return Box::new(HyperTransport::new(config));
+    #[cfg(not(any(feature = "ureq", feature = "reqwest", feature = "hyper")))]
+    {
+        let _ = config;
+        Box::new(FailedTransport(Error::HttpRequest(
+            "no HTTP transport: enable the ureq, reqwest, or hyper feature or set one on the builder"
+                .into(),
+        )))
+    }
+}
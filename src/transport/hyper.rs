@@ -0,0 +1,181 @@
+use std::io::{self, Read};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use bytes::{Buf, Bytes};
+use http_body_util::{BodyExt, Empty};
+use hyper::body::Incoming;
+use hyper_util::client::legacy::connect::HttpConnector;
+use hyper_util::client::legacy::Client;
+use hyper_util::rt::TokioExecutor;
+
+use super::{ImdsTransport, Method, Request, Response, StreamingResponse, TransportConfig};
+use crate::{Error, Result};
+
+/// A transport backed by a `hyper` client, driven by a private
+/// single-threaded tokio runtime.
+///
+/// Requests block the calling thread, so this must not be used from within
+/// an async context. Proxies aren't supported; `TransportConfig::proxy` is
+/// ignored. hyper's pooled client doesn't separate connecting from
+/// reading, so the read timeout is applied to the request as a whole, on
+/// top of the connect timeout.
+#[derive(Debug)]
+pub struct HyperTransport {
+    runtime: Arc<tokio::runtime::Runtime>,
+    client: Client<HttpConnector, Empty<Bytes>>,
+    timeout: Duration,
+}
+
+impl HyperTransport {
+    pub fn new(config: &TransportConfig) -> Self {
+        let timeout = config.connect_timeout + config.read_timeout;
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()
+            .expect("failed to build tokio runtime");
+
+        let mut connector = HttpConnector::new();
+        connector.set_connect_timeout(Some(config.connect_timeout));
+        let client = Client::builder(TokioExecutor::new())
+            .pool_max_idle_per_host(config.idle_connections())
+            .pool_idle_timeout(config.idle_timeout)
+            .build(connector);
+
+        Self {
+            runtime: Arc::new(runtime),
+            client,
+            timeout,
+        }
+    }
+
+    async fn send_async(&self, request: &Request) -> Result<hyper::Response<Incoming>> {
+        let method = match request.method {
+            Method::Get => hyper::Method::GET,
+            Method::Put => hyper::Method::PUT,
+        };
+        let mut builder = hyper::Request::builder().method(method).uri(&request.url);
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+        let req = builder
+            .body(Empty::new())
+            .map_err(|e| Error::HttpRequest(format!("{:?}", e)))?;
+
+        Ok(self.client.request(req).await?)
+    }
+}
+
+// hyper_util reports a connect timeout as a connect error, with the
+// `TimedOut` I/O error somewhere down its source chain.
+fn timed_out(error: &(dyn std::error::Error + 'static)) -> bool {
+    let mut source = Some(error);
+    while let Some(error) = source {
+        if let Some(io_error) = error.downcast_ref::<io::Error>() {
+            if io_error.kind() == io::ErrorKind::TimedOut {
+                return true;
+            }
+        }
+        source = error.source();
+    }
+    false
+}
+
+impl From<hyper_util::client::legacy::Error> for Error {
+    fn from(error: hyper_util::client::legacy::Error) -> Error {
+        if timed_out(&error) {
+            Error::Timeout(format!("{:?}", error))
+        } else {
+            Error::HttpRequest(format!("{:?}", error))
+        }
+    }
+}
+
+// Reads a hyper body a frame at a time, on the transport's runtime. The
+// request's timeout covers the body too, as it would when buffering it.
+struct HyperBody {
+    runtime: Arc<tokio::runtime::Runtime>,
+    body: Incoming,
+    frame: Bytes,
+    deadline: Instant,
+}
+
+impl Read for HyperBody {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while !self.frame.has_remaining() {
+            let remaining = self.deadline.saturating_duration_since(Instant::now());
+            let body = &mut self.body;
+            let frame = self
+                .runtime
+                .block_on(async { tokio::time::timeout(remaining, body.frame()).await })
+                .map_err(|_| io::Error::new(io::ErrorKind::TimedOut, "timed out reading body"))?;
+            match frame {
+                None => return Ok(0),
+                Some(Ok(frame)) => {
+                    // Trailers carry no data, so are skipped.
+                    if let Ok(data) = frame.into_data() {
+                        self.frame = data;
+                    }
+                }
+                Some(Err(e)) => return Err(io::Error::other(e)),
+            }
+        }
+
+        let n = buf.len().min(self.frame.remaining());
+        self.frame.copy_to_slice(&mut buf[..n]);
+        Ok(n)
+    }
+}
+
+impl ImdsTransport for HyperTransport {
+    fn send(&self, request: &Request) -> Result<Response> {
+        let mut resp = self.send_streaming(request)?;
+        let mut body = Vec::new();
+        resp.body.read_to_end(&mut body).map_err(|e| {
+            if e.kind() == io::ErrorKind::TimedOut {
+                Error::Timeout(e.to_string())
+            } else {
+                Error::from(e)
+            }
+        })?;
+
+        Ok(Response {
+            status: resp.status,
+            headers: resp.headers,
+            body,
+        })
+    }
+
+    fn send_streaming(&self, request: &Request) -> Result<StreamingResponse> {
+        let timeout = request
+            .timeout
+            .map_or(self.timeout, |t| t.min(self.timeout));
+        let deadline = Instant::now() + timeout;
+        let resp = self.runtime.block_on(async {
+            tokio::time::timeout(timeout, self.send_async(request))
+                .await
+                .map_err(|_| Error::Timeout(format!("no response after {:?}", timeout)))?
+        })?;
+
+        let status = resp.status().as_u16();
+        let headers = resp
+            .headers()
+            .iter()
+            .map(|(name, value)| {
+                let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+                (name.as_str().to_string(), value)
+            })
+            .collect();
+
+        Ok(StreamingResponse {
+            status,
+            headers,
+            body: Box::new(HyperBody {
+                runtime: self.runtime.clone(),
+                body: resp.into_body(),
+                frame: Bytes::new(),
+                deadline,
+            }),
+        })
+    }
+}
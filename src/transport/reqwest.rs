@@ -0,0 +1,112 @@
+use super::{
+    ImdsTransport, Method, ProxyMode, Request, Response, StreamingResponse, TransportConfig,
+};
+use crate::{Error, Result};
+
+/// A transport backed by a blocking `reqwest::Client`.
+///
+/// `reqwest`'s blocking client runs its own runtime, so this must not be
+/// used from within an async context.
+#[derive(Debug)]
+pub struct ReqwestTransport {
+    client: reqwest::blocking::Client,
+}
+
+impl ReqwestTransport {
+    /// # Panics
+    /// Panics if `config.proxy` is a URL reqwest can't parse. See
+    /// `try_new`.
+    pub fn new(config: &TransportConfig) -> Self {
+        Self::try_new(config).expect("failed to build reqwest client")
+    }
+
+    /// Like `new`, but fails with `Error::InvalidValue` if `config.proxy`
+    /// is a URL reqwest can't parse.
+    pub fn try_new(config: &TransportConfig) -> Result<Self> {
+        // The blocking client has no read timeout of its own, only a total
+        // one, so allow for both connecting and reading.
+        let builder = reqwest::blocking::Client::builder()
+            .connect_timeout(config.connect_timeout)
+            .timeout(config.connect_timeout + config.read_timeout)
+            .pool_max_idle_per_host(config.idle_connections())
+            .pool_idle_timeout(config.idle_timeout);
+
+        // reqwest reads the proxy environment variables by default.
+        let builder = match &config.proxy {
+            ProxyMode::Bypass => builder.no_proxy(),
+            ProxyMode::Auto | ProxyMode::Environment => builder,
+            ProxyMode::Url(url) => {
+                let proxy = reqwest::Proxy::all(url)
+                    .map_err(|e| Error::InvalidValue(format!("proxy URL {:?}: {}", url, e)))?;
+                builder.proxy(proxy)
+            }
+        };
+
+        Ok(Self {
+            client: builder.build()?,
+        })
+    }
+
+    pub fn from_client(client: reqwest::blocking::Client) -> Self {
+        Self { client }
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(error: reqwest::Error) -> Error {
+        if error.is_timeout() {
+            Error::Timeout(format!("{:?}", error))
+        } else {
+            Error::HttpRequest(format!("{:?}", error))
+        }
+    }
+}
+
+impl ReqwestTransport {
+    fn call(&self, request: &Request) -> Result<reqwest::blocking::Response> {
+        let mut req = match request.method {
+            Method::Get => self.client.get(&request.url),
+            Method::Put => self.client.put(&request.url),
+        };
+        for (name, value) in &request.headers {
+            req = req.header(name.as_str(), value.as_str());
+        }
+        if let Some(timeout) = request.timeout {
+            req = req.timeout(timeout);
+        }
+
+        Ok(req.send()?)
+    }
+}
+
+fn headers(resp: &reqwest::blocking::Response) -> Vec<(String, String)> {
+    resp.headers()
+        .iter()
+        .map(|(name, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+            (name.as_str().to_string(), value)
+        })
+        .collect()
+}
+
+impl ImdsTransport for ReqwestTransport {
+    fn send(&self, request: &Request) -> Result<Response> {
+        let resp = self.call(request)?;
+
+        Ok(Response {
+            status: resp.status().as_u16(),
+            headers: headers(&resp),
+            body: resp.bytes()?.to_vec(),
+        })
+    }
+
+    fn send_streaming(&self, request: &Request) -> Result<StreamingResponse> {
+        let resp = self.call(request)?;
+
+        Ok(StreamingResponse {
+            status: resp.status().as_u16(),
+            headers: headers(&resp),
+            body: Box::new(resp),
+        })
+    }
+}
@@ -0,0 +1,121 @@
+use super::{
+    ImdsTransport, Method, ProxyMode, Request, Response, StreamingResponse, TransportConfig,
+};
+use crate::{Error, Result};
+
+/// The default transport, backed by a `ureq::Agent`.
+#[derive(Debug)]
+pub struct UreqTransport {
+    agent: ureq::Agent,
+}
+
+impl UreqTransport {
+    /// # Panics
+    /// Panics if `config.proxy` is a URL ureq can't parse. See `try_new`.
+    pub fn new(config: &TransportConfig) -> Self {
+        Self::try_new(config).expect("invalid proxy URL")
+    }
+
+    /// Like `new`, but fails with `Error::InvalidValue` if `config.proxy`
+    /// is a URL ureq can't parse.
+    pub fn try_new(config: &TransportConfig) -> Result<Self> {
+        let proxy = match &config.proxy {
+            ProxyMode::Bypass => None,
+            ProxyMode::Auto | ProxyMode::Environment => ureq::Proxy::try_from_env(),
+            ProxyMode::Url(url) => Some(
+                ureq::Proxy::new(url)
+                    .map_err(|e| Error::InvalidValue(format!("proxy URL {:?}: {}", url, e)))?,
+            ),
+        };
+
+        let agent = ureq::Agent::config_builder()
+            .timeout_connect(Some(config.connect_timeout))
+            .timeout_recv_response(Some(config.read_timeout))
+            .timeout_recv_body(Some(config.read_timeout))
+            .proxy(proxy)
+            .max_idle_connections(config.idle_connections())
+            .max_idle_connections_per_host(config.idle_connections())
+            .max_idle_age(config.idle_timeout)
+            .http_status_as_error(false)
+            .build()
+            .new_agent();
+
+        Ok(Self { agent })
+    }
+
+    /// Wrap an existing agent. Note the agent must be configured with
+    /// `http_status_as_error(false)` for 404s to be reported correctly.
+    pub fn from_agent(agent: ureq::Agent) -> Self {
+        Self { agent }
+    }
+}
+
+impl From<ureq::Error> for Error {
+    fn from(error: ureq::Error) -> Error {
+        match error {
+            ureq::Error::Timeout(timeout) => Error::Timeout(format!("{:?}", timeout)),
+            error => Error::HttpRequest(format!("{:?}", error)),
+        }
+    }
+}
+
+impl UreqTransport {
+    fn call(&self, request: &Request) -> Result<ureq::http::Response<ureq::Body>> {
+        let resp = match request.method {
+            Method::Get => {
+                let mut req = self.agent.get(&request.url);
+                for (name, value) in &request.headers {
+                    req = req.header(name, value);
+                }
+                if let Some(timeout) = request.timeout {
+                    req = req.config().timeout_global(Some(timeout)).build();
+                }
+                req.call()?
+            }
+            Method::Put => {
+                let mut req = self.agent.put(&request.url);
+                for (name, value) in &request.headers {
+                    req = req.header(name, value);
+                }
+                if let Some(timeout) = request.timeout {
+                    req = req.config().timeout_global(Some(timeout)).build();
+                }
+                req.send_empty()?
+            }
+        };
+
+        Ok(resp)
+    }
+}
+
+fn headers(resp: &ureq::http::Response<ureq::Body>) -> Vec<(String, String)> {
+    resp.headers()
+        .iter()
+        .map(|(name, value)| {
+            let value = String::from_utf8_lossy(value.as_bytes()).into_owned();
+            (name.as_str().to_string(), value)
+        })
+        .collect()
+}
+
+impl ImdsTransport for UreqTransport {
+    fn send(&self, request: &Request) -> Result<Response> {
+        let mut resp = self.call(request)?;
+
+        Ok(Response {
+            status: resp.status().as_u16(),
+            headers: headers(&resp),
+            body: resp.body_mut().read_to_vec()?,
+        })
+    }
+
+    fn send_streaming(&self, request: &Request) -> Result<StreamingResponse> {
+        let resp = self.call(request)?;
+
+        Ok(StreamingResponse {
+            status: resp.status().as_u16(),
+            headers: headers(&resp),
+            body: Box::new(resp.into_body().into_reader()),
+        })
+    }
+}
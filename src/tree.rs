@@ -0,0 +1,121 @@
+use std::collections::BTreeMap;
+
+use crate::{InstanceMetadataClient, Result};
+
+// IMDS lists a directory as one entry per line. Subdirectories end with a
+// slash, except in listings like `public-keys/` whose entries look like
+// `0=my-key` and name the subdirectory `0/`.
+fn parse_listing(listing: &str) -> Vec<String> {
+    listing
+        .lines()
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| match entry.split_once('=') {
+            Some((index, _)) => format!("{}/", index),
+            None => entry.to_string(),
+        })
+        .collect()
+}
+
+// The root listing names these without a trailing slash.
+const ROOT_DIRECTORIES: &[&str] = &["dynamic", "meta-data"];
+
+fn directory(prefix: &str) -> String {
+    let prefix = prefix.trim_matches('/');
+    if prefix.is_empty() {
+        String::new()
+    } else {
+        format!("{}/", prefix)
+    }
+}
+
+impl InstanceMetadataClient {
+    /// List the keys available under `prefix`, relative to `/latest/`
+    /// (e.g. `meta-data`). Entries ending in `/` are subtrees which can be
+    /// listed in turn.
+    ///
+    /// # Examples:
+    /// ```no_run
+    /// use ec2_instance_metadata::InstanceMetadataClient;
+    /// let client = InstanceMetadataClient::new();
+    /// for key in client.list_keys("meta-data").unwrap() {
+    ///     println!("{}", key);
+    /// }
+    /// ```
+    ///
+    /// Entries of the `public-keys/` listing, like `0=my-key`, are returned as
+    /// the subdirectory they name:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set("meta-data/public-keys", "0=my-key");
+    /// server.set("meta-data/public-keys/0/openssh-key", "ssh-ed25519 AAAA my-key");
+    /// let client = server.client();
+    ///
+    /// assert_eq!(client.list_keys("").unwrap(), ["dynamic/", "meta-data/"]);
+    /// assert_eq!(
+    ///     client.list_keys("meta-data/placement").unwrap(),
+    ///     ["availability-zone", "availability-zone-id", "region"]
+    /// );
+    /// assert_eq!(client.list_keys("meta-data/public-keys").unwrap(), ["0/"]);
+    /// # }
+    /// ```
+    pub fn list_keys(&self, prefix: &str) -> Result<Vec<String>> {
+        Ok(parse_listing(&self.get_path(&directory(prefix))?))
+    }
+
+    /// Fetch every value under `prefix`, walking subtrees recursively. Keys
+    /// of the returned map are full paths relative to `/latest/`. An empty
+    /// `prefix` fetches both `meta-data/` and `dynamic/`, and the user data
+    /// if there is any.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// server.set("meta-data/public-keys", "0=my-key");
+    /// server.set("meta-data/public-keys/0/openssh-key", "ssh-ed25519 AAAA my-key");
+    /// let client = server.client();
+    ///
+    /// let placement = client.get_tree("meta-data/placement").unwrap();
+    /// assert_eq!(placement.len(), 3);
+    /// assert_eq!(placement["meta-data/placement/region"], "us-east-1");
+    ///
+    /// let keys = client.get_tree("meta-data/public-keys").unwrap();
+    /// assert_eq!(keys.len(), 1);
+    /// assert_eq!(
+    ///     keys["meta-data/public-keys/0/openssh-key"],
+    ///     "ssh-ed25519 AAAA my-key"
+    /// );
+    ///
+    /// let everything = client.get_tree("").unwrap();
+    /// assert_eq!(everything["dynamic/fws/instance-monitoring"], "disabled");
+    /// assert_eq!(everything["meta-data/instance-type"], "t3.micro");
+    /// # }
+    /// ```
+    pub fn get_tree(&self, prefix: &str) -> Result<BTreeMap<String, String>> {
+        let token = self.get_token()?;
+        let mut tree = BTreeMap::new();
+        let mut pending = vec![directory(prefix)];
+
+        while let Some(dir) = pending.pop() {
+            for entry in parse_listing(&self.fetch(&dir, &token)?) {
+                let path = if dir.is_empty() && ROOT_DIRECTORIES.contains(&entry.as_str()) {
+                    format!("{}/", entry)
+                } else {
+                    format!("{}{}", dir, entry)
+                };
+                if path.ends_with('/') {
+                    pending.push(path);
+                } else {
+                    let value = self.fetch(&path, &token)?;
+                    tree.insert(path, value);
+                }
+            }
+        }
+
+        Ok(tree)
+    }
+}
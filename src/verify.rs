@@ -0,0 +1,122 @@
+use std::fmt;
+
+use crate::account::IDENTITY_DOCUMENT;
+use crate::{flat_json, InstanceMetadata, InstanceMetadataClient, Result};
+
+// The fields the identity document also has, by their names in
+// `InstanceMetadata::fields` and in the document.
+const CHECKED_FIELDS: &[(&str, &str)] = &[
+    ("instance-id", "instanceId"),
+    ("account-id", "accountId"),
+    ("ami-id", "imageId"),
+    ("instance-type", "instanceType"),
+    ("region", "region"),
+    ("availability-zone", "availabilityZone"),
+];
+
+/// A field whose value in the metadata differs from the identity
+/// document's. `None` means the document doesn't have the field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldMismatch {
+    /// The field's kebab-case name, as in `InstanceMetadata::fields`.
+    pub field: &'static str,
+    pub metadata: String,
+    pub identity_document: Option<String>,
+}
+
+/// `ConsistencyReport` is the result of checking the metadata against the
+/// instance identity document. See
+/// `InstanceMetadataClient::get_verified`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConsistencyReport {
+    pub metadata: InstanceMetadata,
+    pub mismatches: Vec<FieldMismatch>,
+}
+
+impl ConsistencyReport {
+    /// True if every field the identity document has agrees with the
+    /// metadata.
+    pub fn is_consistent(&self) -> bool {
+        self.mismatches.is_empty()
+    }
+
+    /// The mismatch for `field`, if it doesn't match.
+    pub fn get(&self, field: &str) -> Option<&FieldMismatch> {
+        self.mismatches
+            .iter()
+            .find(|mismatch| mismatch.field == field)
+    }
+}
+
+/// One `field: metadata != identity document` line per mismatch, with `-`
+/// for a field missing from the document.
+impl fmt::Display for ConsistencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, mismatch) in self.mismatches.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            let documented = mismatch.identity_document.as_deref().unwrap_or("-");
+            write!(
+                f,
+                "{}: {} != {}",
+                mismatch.field, mismatch.metadata, documented
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl InstanceMetadataClient {
+    /// Get the instance metadata and check it against the instance
+    /// identity document, which IMDS serves as a single signed document.
+    /// A well-behaved IMDS always agrees with itself, so mismatches point
+    /// at a tampering or misconfigured metadata proxy. Always goes to
+    /// IMDS, bypassing the cache.
+    ///
+    /// Only fields the metadata has are checked. If the account id was
+    /// read from the identity document (see `AccountIdSource`), it
+    /// trivially matches.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// assert!(server.client().get_verified().unwrap().is_consistent());
+    ///
+    /// server.set("meta-data/instance-type", "m5.24xlarge");
+    /// let report = server.client().get_verified().unwrap();
+    /// let mismatch = report.get("instance-type").unwrap();
+    /// assert_eq!(mismatch.identity_document.as_deref(), Some("t3.micro"));
+    /// assert_eq!(report.to_string(), "instance-type: m5.24xlarge != t3.micro");
+    /// # }
+    /// ```
+    pub fn get_verified(&self) -> Result<ConsistencyReport> {
+        let metadata = self.fetch_metadata()?;
+        let token = self.get_token()?;
+        let document = self.fetch_within(IDENTITY_DOCUMENT, &token, self.operation_deadline())?;
+
+        let fields = metadata.fields();
+        let mut mismatches = Vec::new();
+        for (field, key) in CHECKED_FIELDS {
+            let value = match fields.iter().find(|(name, _)| name == field) {
+                Some((_, value)) => value,
+                None => continue,
+            };
+            let documented = flat_json::string_field(&document, key)?;
+            if documented.as_deref() != Some(value.as_str()) {
+                mismatches.push(FieldMismatch {
+                    field,
+                    metadata: value.clone(),
+                    identity_document: documented,
+                });
+            }
+        }
+
+        Ok(ConsistencyReport {
+            metadata,
+            mismatches,
+        })
+    }
+}
@@ -0,0 +1,167 @@
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+use arc_swap::ArcSwap;
+
+use crate::{Error, InstanceMetadata, InstanceMetadataClient, Result};
+
+// Events beyond this are dropped if nobody is reading them; the latest
+// snapshot is always available through `Watcher::current`.
+const EVENT_BUFFER: usize = 16;
+
+/// An event emitted by a `Watcher` after a refresh.
+#[derive(Debug, Clone)]
+pub enum WatchEvent<T> {
+    /// The refreshed value differs from the previous one.
+    Changed { previous: Arc<T>, current: Arc<T> },
+    /// The refresh failed; the previous value is kept.
+    RefreshFailed(Error),
+}
+
+/// `Watcher` re-fetches a value on an interval in a background thread and
+/// publishes the latest snapshot. The thread exits when the watcher is
+/// stopped or dropped, or once its client's `CancellationToken` is
+/// cancelled.
+#[derive(Debug)]
+pub struct Watcher<T> {
+    current: Arc<ArcSwap<T>>,
+    events: Receiver<WatchEvent<T>>,
+    // Dropping the sender wakes the background thread and tells it to exit.
+    stop: Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+/// `MetadataWatcher` keeps an up-to-date `InstanceMetadata` snapshot.
+///
+/// # Examples:
+/// ```no_run
+/// use std::time::Duration;
+/// use ec2_instance_metadata::InstanceMetadataClient;
+/// let client = InstanceMetadataClient::new();
+/// let watcher = client.watch(Duration::from_secs(60)).unwrap();
+/// println!("starting on {}", watcher.current().instance_id);
+/// for event in watcher.events() {
+///     if let Some(diff) = event.diff() {
+///         println!("metadata changed:\n{}", diff);
+///     }
+/// }
+/// ```
+pub type MetadataWatcher = Watcher<InstanceMetadata>;
+
+impl<T> Watcher<T>
+where
+    T: PartialEq + Send + Sync + 'static,
+{
+    /// Performs the first fetch on the calling thread so a watcher always
+    /// starts with a value, then refreshes every `interval`.
+    pub(crate) fn spawn<F>(interval: Duration, mut fetch: F) -> Result<Self>
+    where
+        F: FnMut() -> Result<T> + Send + 'static,
+    {
+        let initial = fetch()?;
+        Ok(Self::start(initial, interval, interval, fetch))
+    }
+
+    /// Starts with `initial` and fetches right away in the background, so
+    /// a failed first fetch is retried like any other.
+    pub(crate) fn spawn_from<F>(initial: T, interval: Duration, fetch: F) -> Self
+    where
+        F: FnMut() -> Result<T> + Send + 'static,
+    {
+        Self::start(initial, Duration::ZERO, interval, fetch)
+    }
+
+    fn start<F>(initial: T, first_wait: Duration, interval: Duration, mut fetch: F) -> Self
+    where
+        F: FnMut() -> Result<T> + Send + 'static,
+    {
+        let current = Arc::new(ArcSwap::from_pointee(initial));
+        let (events_tx, events) = mpsc::sync_channel(EVENT_BUFFER);
+        let (stop, stop_rx) = mpsc::channel();
+
+        let snapshot = Arc::clone(&current);
+        let thread = thread::spawn(move || {
+            let mut wait = first_wait;
+            while let Err(RecvTimeoutError::Timeout) = stop_rx.recv_timeout(wait) {
+                wait = interval;
+                let event = match fetch() {
+                    Ok(value) => {
+                        if **snapshot.load() == value {
+                            continue;
+                        }
+                        let current = Arc::new(value);
+                        let previous = snapshot.swap(Arc::clone(&current));
+                        WatchEvent::Changed { previous, current }
+                    }
+                    Err(Error::Cancelled) => break,
+                    Err(e) => WatchEvent::RefreshFailed(e),
+                };
+                if !send_event(&events_tx, event) {
+                    break;
+                }
+            }
+        });
+
+        Self {
+            current,
+            events,
+            stop,
+            thread,
+        }
+    }
+}
+
+// Returns false once the watcher (and its receiver) is gone.
+fn send_event<T>(events: &SyncSender<WatchEvent<T>>, event: WatchEvent<T>) -> bool {
+    !matches!(events.try_send(event), Err(TrySendError::Disconnected(_)))
+}
+
+impl<T> Watcher<T> {
+    /// The most recently fetched value.
+    pub fn current(&self) -> Arc<T> {
+        self.current.load_full()
+    }
+
+    /// A shared handle to the snapshot, updated in place by the background
+    /// thread. Useful for handing to other threads without the watcher.
+    pub fn snapshot(&self) -> Arc<ArcSwap<T>> {
+        Arc::clone(&self.current)
+    }
+
+    /// Change and error events, in the order they happened. Iterating
+    /// ends once the background thread has exited.
+    pub fn events(&self) -> &Receiver<WatchEvent<T>> {
+        &self.events
+    }
+
+    /// Stop refreshing and wait for the background thread to exit. A
+    /// refresh already under way is waited for; cancel the client's
+    /// `CancellationToken` first to cut it short.
+    ///
+    /// # Examples:
+    /// ```
+    /// # #[cfg(feature = "mock")] {
+    /// use std::time::Duration;
+    /// use ec2_instance_metadata::mock::MockImdsServer;
+    /// let server = MockImdsServer::start().unwrap();
+    /// let watcher = server.client().watch(Duration::from_secs(3600)).unwrap();
+    /// watcher.stop();
+    /// # }
+    /// ```
+    pub fn stop(self) {
+        drop(self.stop);
+        // The thread only panics if `fetch` did, which was already
+        // reported when it happened.
+        let _ = self.thread.join();
+    }
+}
+
+impl InstanceMetadataClient {
+    /// Start a `MetadataWatcher` which refreshes the instance metadata every
+    /// `interval`. Refreshes always go to IMDS, bypassing the cache.
+    pub fn watch(self, interval: Duration) -> Result<MetadataWatcher> {
+        Watcher::spawn(interval, move || self.fetch_metadata())
+    }
+}
@@ -0,0 +1,230 @@
+use std::fmt;
+use std::str::FromStr;
+
+#[cfg(feature = "json")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "json")]
+use crate::region::looks_like_region;
+use crate::region::REGIONS;
+use crate::{Error, Region, Result};
+
+/// The kind of zone an instance runs in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ZoneKind {
+    /// A regular availability zone, e.g. `us-east-1a`
+    Standard,
+    /// A Local Zone in a metro area away from the region, e.g.
+    /// `us-east-1-bos-1a`
+    LocalZone,
+    /// A Wavelength Zone in a carrier's network, e.g.
+    /// `us-east-1-wl1-atl-wlz-1`
+    WavelengthZone,
+    /// An Outposts rack or server anchored to the zone. Outposts use the
+    /// name of their parent zone, so this can't be told from the name;
+    /// it's only known from the instance's placement.
+    Outpost,
+}
+
+impl ZoneKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ZoneKind::Standard => "standard",
+            ZoneKind::LocalZone => "local-zone",
+            ZoneKind::WavelengthZone => "wavelength-zone",
+            ZoneKind::Outpost => "outpost",
+        }
+    }
+}
+
+impl fmt::Display for ZoneKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+fn is_lower_alpha(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_lowercase())
+}
+
+fn is_digits(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_digit())
+}
+
+// What follows the region name in a zone name: a letter (`a`), a metro
+// and a numbered zone (`-bos-1a`), or a carrier, metro and Wavelength
+// zone (`-wl1-atl-wlz-1`).
+fn zone_suffix_kind(suffix: &str) -> Option<ZoneKind> {
+    if suffix.len() == 1 && is_lower_alpha(suffix) {
+        return Some(ZoneKind::Standard);
+    }
+
+    let parts: Vec<&str> = suffix.strip_prefix('-')?.split('-').collect();
+    match parts.as_slice() {
+        [carrier, metro, "wlz", number]
+            if carrier.strip_prefix("wl").is_some_and(is_digits)
+                && is_lower_alpha(metro)
+                && is_digits(number) =>
+        {
+            Some(ZoneKind::WavelengthZone)
+        }
+        [metro, zone] if is_lower_alpha(metro) && zone.len() >= 2 => {
+            let (number, letter) = zone.split_at(zone.len() - 1);
+            if is_digits(number) && is_lower_alpha(letter) {
+                Some(ZoneKind::LocalZone)
+            } else {
+                None
+            }
+        }
+        _ => None,
+    }
+}
+
+/// `AvailabilityZone` is a validated zone name, along with the region it
+/// belongs to and what kind of zone it is.
+///
+/// # Examples:
+/// ```
+/// use ec2_instance_metadata::{AvailabilityZone, ZoneKind};
+/// let zone = |name: &str| name.parse::<AvailabilityZone>().unwrap();
+///
+/// assert_eq!(zone("us-east-1a").kind(), ZoneKind::Standard);
+/// assert_eq!(zone("us-east-1a").region(), "us-east-1");
+///
+/// assert_eq!(zone("us-east-1-bos-1a").kind(), ZoneKind::LocalZone);
+/// assert_eq!(zone("us-east-1-bos-1a").region(), "us-east-1");
+/// assert_eq!(zone("us-west-2-lax-1b").region(), "us-west-2");
+///
+/// assert_eq!(zone("us-east-1-wl1-atl-wlz-1").kind(), ZoneKind::WavelengthZone);
+/// assert_eq!(zone("us-east-1-wl1-atl-wlz-1").region(), "us-east-1");
+/// assert_eq!(zone("ap-northeast-1-wl1-kix-wlz-1").region(), "ap-northeast-1");
+///
+/// assert_eq!(zone("us-gov-west-1a").region(), "us-gov-west-1");
+///
+/// assert!("us-east-1".parse::<AvailabilityZone>().is_err());
+/// assert!("us-east-1-bos".parse::<AvailabilityZone>().is_err());
+/// assert!("us-east-9a".parse::<AvailabilityZone>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct AvailabilityZone {
+    name: String,
+    region: Region,
+    kind: ZoneKind,
+}
+
+impl AvailabilityZone {
+    /// The zone name, e.g. `us-east-1a`.
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    pub fn region(&self) -> Region {
+        self.region.clone()
+    }
+
+    // A zone in `region`, which IMDS reported and this crate may not list.
+    pub(crate) fn in_region(name: &str, region: Region) -> Result<Self> {
+        let name = name.trim();
+        let kind = name
+            .strip_prefix(region.name())
+            .and_then(zone_suffix_kind)
+            .ok_or_else(|| Error::UnknownAvailabilityZone(name.to_string()))?;
+        Ok(AvailabilityZone {
+            name: name.to_string(),
+            region,
+            kind,
+        })
+    }
+
+    pub fn kind(&self) -> ZoneKind {
+        self.kind
+    }
+
+    // Outposts can't be told from the zone name; see `ZoneKind::Outpost`.
+    pub(crate) fn on_outpost(mut self) -> Self {
+        self.kind = ZoneKind::Outpost;
+        self
+    }
+}
+
+impl FromStr for AvailabilityZone {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        // Try the longest region names first, so a region whose name is a
+        // prefix of another's can't shadow it.
+        let mut candidates: Vec<&str> = REGIONS
+            .iter()
+            .map(|(name, _)| *name)
+            .filter(|name| s.starts_with(name))
+            .collect();
+        candidates.sort_by_key(|name| std::cmp::Reverse(name.len()));
+
+        candidates
+            .into_iter()
+            .find_map(|name| {
+                let kind = zone_suffix_kind(&s[name.len()..])?;
+                Some(AvailabilityZone {
+                    name: s.to_string(),
+                    region: name.parse().ok()?,
+                    kind,
+                })
+            })
+            .ok_or_else(|| Error::UnknownAvailabilityZone(s.to_string()))
+    }
+}
+
+impl AsRef<str> for AvailabilityZone {
+    fn as_ref(&self) -> &str {
+        &self.name
+    }
+}
+
+impl PartialEq<str> for AvailabilityZone {
+    fn eq(&self, other: &str) -> bool {
+        self.name == other
+    }
+}
+
+impl PartialEq<&str> for AvailabilityZone {
+    fn eq(&self, other: &&str) -> bool {
+        self.name == *other
+    }
+}
+
+impl From<AvailabilityZone> for String {
+    fn from(zone: AvailabilityZone) -> String {
+        zone.name
+    }
+}
+
+impl fmt::Display for AvailabilityZone {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+#[cfg(feature = "json")]
+impl Serialize for AvailabilityZone {
+    fn serialize<S: Serializer>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.name)
+    }
+}
+
+#[cfg(feature = "json")]
+impl<'de> Deserialize<'de> for AvailabilityZone {
+    // Snapshots can hold zones in regions this crate doesn't list, so any
+    // zone whose name starts with something shaped like a region is
+    // accepted.
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> std::result::Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        if let Ok(zone) = s.parse() {
+            return Ok(zone);
+        }
+        s.char_indices()
+            .map(|(i, _)| &s[..i])
+            .filter(|region| looks_like_region(region))
+            .find_map(|region| Self::in_region(&s, Region::reported(region, None)).ok())
+            .ok_or_else(|| serde::de::Error::custom(Error::UnknownAvailabilityZone(s.clone())))
+    }
+}
@@ -0,0 +1,78 @@
+//! Runs the client against a real IMDS simulator rather than the crate's
+//! own mock. The tests are ignored by default; start a simulator, e.g.
+//!
+//! ```text
+//! $ ec2-metadata-mock --port 1338
+//! $ EC2_METADATA_SIMULATOR=http://127.0.0.1:1338 cargo test --test simulator -- --ignored
+//! ```
+//!
+//! They only assume what every simulator serves, so they also pass against
+//! LocalStack or a real instance.
+
+use ec2_instance_metadata::{Availability, Error, InstanceMetadataClient};
+
+const SIMULATOR_VAR: &str = "EC2_METADATA_SIMULATOR";
+
+fn client() -> InstanceMetadataClient {
+    let endpoint = std::env::var(SIMULATOR_VAR)
+        .unwrap_or_else(|_| panic!("set {} to the simulator's endpoint", SIMULATOR_VAR));
+    InstanceMetadataClient::builder()
+        .simulator(endpoint)
+        .build()
+}
+
+#[test]
+#[ignore]
+fn get_matches_individual_paths() {
+    let client = client();
+    let metadata = client.get().unwrap();
+
+    assert_eq!(
+        client.get_path("meta-data/instance-id").unwrap(),
+        metadata.instance_id.as_str()
+    );
+    assert_eq!(
+        client.get_path("meta-data/instance-type").unwrap(),
+        metadata.instance_type.to_string()
+    );
+    assert!(metadata
+        .availability_zone
+        .as_str()
+        .starts_with(metadata.region.as_ref()));
+}
+
+#[test]
+#[ignore]
+fn missing_paths_are_not_found() {
+    let result = client().get_path("meta-data/no-such-category");
+    assert!(matches!(result, Err(Error::NotFound(_))), "{:?}", result);
+}
+
+#[test]
+#[ignore]
+fn raw_responses_carry_the_status() {
+    let client = client();
+    assert_eq!(client.get_raw("meta-data/instance-id").unwrap().status, 200);
+    assert_eq!(
+        client.get_raw("meta-data/no-such-category").unwrap().status,
+        404
+    );
+}
+
+#[test]
+#[ignore]
+fn meta_data_lists_instance_id() {
+    let keys = client().list_keys("meta-data").unwrap();
+    assert!(keys.iter().any(|key| key == "instance-id"), "{:?}", keys);
+}
+
+#[test]
+#[ignore]
+fn simulator_is_available() {
+    let endpoint = std::env::var(SIMULATOR_VAR).unwrap();
+    let client = InstanceMetadataClient::builder().endpoint(endpoint).build();
+    assert!(matches!(
+        client.ping(),
+        Availability::Available { .. } | Availability::V1Only
+    ));
+}